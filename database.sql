@@ -1,9 +1,25 @@
+drop table if exists template_item;
+drop table if exists template;
+drop table if exists history;
+drop table if exists todo_item_tag;
+drop table if exists tag;
 drop table if exists todo_item;
 drop table if exists todo_list;
+drop type if exists item_priority;
+drop function if exists bump_version();
+drop function if exists record_history();
+
+create type item_priority as enum ('none', 'low', 'medium', 'high', 'urgent');
 
 create table todo_list (
     id serial primary key,
-    title varchar(150)
+    title varchar(150),
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    search tsvector generated always as (to_tsvector('english', coalesce(title, ''))) stored,
+    deleted_at timestamptz,
+    archived boolean not null default false,
+    version integer not null default 1
 );
 
 create table todo_item (
@@ -11,9 +27,134 @@ create table todo_item (
     title varchar(150) not null,
     checked boolean not null default false,
     list_id integer not null,
-    foreign key (list_id) references todo_list(id)
+    created_at timestamptz not null default now(),
+    updated_at timestamptz not null default now(),
+    completed_at timestamptz,
+    due_at timestamptz,
+    priority item_priority not null default 'none',
+    parent_id integer,
+    position integer not null,
+    search tsvector generated always as (to_tsvector('english', title)) stored,
+    deleted_at timestamptz,
+    version integer not null default 1,
+    recurrence varchar(100),
+    previous_occurrence_id integer references todo_item(id) on delete set null,
+    foreign key (list_id) references todo_list(id) on delete cascade,
+    constraint todo_item_list_id_id_key unique (list_id, id),
+    constraint todo_item_parent_fkey foreign key (list_id, parent_id)
+        references todo_item (list_id, id) on update cascade on delete cascade,
+    constraint todo_item_list_id_position_key unique (list_id, position)
+        deferrable initially immediate
+);
+
+create table tag (
+    id serial primary key,
+    name varchar(50) not null unique
+);
+
+create table todo_item_tag (
+    item_id integer not null references todo_item(id) on delete cascade,
+    tag_id integer not null references tag(id) on delete cascade,
+    primary key (item_id, tag_id)
+);
+
+create index todo_item_list_id_due_at_idx on todo_item (list_id, due_at);
+create index todo_item_due_at_idx on todo_item (due_at) where due_at is not null;
+create index todo_item_list_id_priority_idx on todo_item (list_id, priority desc, due_at);
+create index todo_item_tag_tag_id_idx on todo_item_tag (tag_id);
+create index todo_item_parent_id_idx on todo_item (parent_id);
+create index todo_item_previous_occurrence_id_idx on todo_item (previous_occurrence_id)
+    where previous_occurrence_id is not null;
+create index todo_list_search_idx on todo_list using gin (search);
+create index todo_item_search_idx on todo_item using gin (search);
+create index todo_list_deleted_at_idx on todo_list (deleted_at) where deleted_at is not null;
+create index todo_item_deleted_at_idx on todo_item (deleted_at) where deleted_at is not null;
+
+create table history (
+    id serial primary key,
+    list_id integer references todo_list(id) on delete cascade,
+    item_id integer references todo_item(id) on delete cascade,
+    action varchar(16) not null,
+    version integer not null,
+    old_values jsonb,
+    new_values jsonb not null,
+    changed_at timestamptz not null default now(),
+    check ((list_id is null) <> (item_id is null))
+);
+
+create table template (
+    id serial primary key,
+    name varchar(150) not null,
+    created_at timestamptz not null default now()
+);
+
+create table template_item (
+    template_id integer not null references template(id) on delete cascade,
+    position integer not null,
+    parent_position integer,
+    title varchar(150) not null,
+    priority item_priority not null default 'none',
+    recurrence varchar(100),
+    primary key (template_id, position),
+    foreign key (template_id, parent_position) references template_item (template_id, position)
 );
 
+create unique index history_list_version_idx on history (list_id, version) where list_id is not null;
+create unique index history_item_version_idx on history (item_id, version) where item_id is not null;
+
+create function bump_version() returns trigger as $$
+begin
+    new.version := old.version + 1;
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger todo_list_bump_version before update on todo_list
+    for each row execute procedure bump_version();
+create trigger todo_item_bump_version before update on todo_item
+    for each row execute procedure bump_version();
+
+create function record_history() returns trigger as $$
+declare
+    ignored text[] := array['search', 'position', 'updated_at', 'version'];
+    old_values jsonb;
+    new_values jsonb := to_jsonb(new) - ignored;
+    action text := 'update';
+begin
+    if tg_op = 'INSERT' then
+        action := 'create';
+    else
+        old_values := to_jsonb(old) - ignored;
+
+        if old_values = new_values then
+            return null;
+        elsif old.deleted_at is null and new.deleted_at is not null then
+            action := 'delete';
+        elsif old.deleted_at is not null and new.deleted_at is null then
+            action := 'restore';
+        elsif old_values -> 'checked' is distinct from new_values -> 'checked' then
+            action := case when (new_values ->> 'checked')::boolean then 'check' else 'uncheck' end;
+        elsif old_values -> 'archived' is distinct from new_values -> 'archived' then
+            action := case when (new_values ->> 'archived')::boolean then 'archive' else 'unarchive' end;
+        end if;
+    end if;
+
+    insert into history (list_id, item_id, action, version, old_values, new_values)
+    values (
+        case when tg_table_name = 'todo_list' then new.id end,
+        case when tg_table_name = 'todo_item' then new.id end,
+        action, new.version, old_values, new_values
+    );
+
+    return null;
+end;
+$$ language plpgsql;
+
+create trigger todo_list_record_history after insert or update on todo_list
+    for each row execute procedure record_history();
+create trigger todo_item_record_history after insert or update on todo_item
+    for each row execute procedure record_history();
+
 insert into todo_list (title) values ('List 1'), ('List 2');
-insert into todo_item (title, list_id) 
-    values ('Connect to database', 1), ('Do queries', 1);
\ No newline at end of file
+insert into todo_item (title, list_id, position)
+    values ('Connect to database', 1, 1), ('Do queries', 1, 2);
\ No newline at end of file
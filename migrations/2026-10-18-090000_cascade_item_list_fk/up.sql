@@ -0,0 +1,4 @@
+alter table todo_item drop constraint todo_item_list_id_fkey;
+alter table todo_item
+    add constraint todo_item_list_id_fkey
+    foreign key (list_id) references todo_list(id) on delete cascade;
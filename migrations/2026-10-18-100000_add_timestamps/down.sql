@@ -0,0 +1,8 @@
+alter table todo_item
+    drop column completed_at,
+    drop column updated_at,
+    drop column created_at;
+
+alter table todo_list
+    drop column updated_at,
+    drop column created_at;
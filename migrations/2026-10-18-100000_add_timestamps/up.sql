@@ -0,0 +1,11 @@
+alter table todo_list
+    add column created_at timestamptz not null default now(),
+    add column updated_at timestamptz not null default now();
+
+alter table todo_item
+    add column created_at timestamptz not null default now(),
+    add column updated_at timestamptz not null default now(),
+    add column completed_at timestamptz;
+
+-- Items that were already checked get a best-effort completion time.
+update todo_item set completed_at = updated_at where checked;
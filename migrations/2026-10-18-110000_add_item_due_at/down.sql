@@ -0,0 +1,4 @@
+drop index if exists todo_item_due_at_idx;
+drop index if exists todo_item_list_id_due_at_idx;
+
+alter table todo_item drop column due_at;
@@ -0,0 +1,5 @@
+alter table todo_item add column due_at timestamptz;
+
+-- Per-list due date filters and the cross-list due/overdue listing.
+create index todo_item_list_id_due_at_idx on todo_item (list_id, due_at);
+create index todo_item_due_at_idx on todo_item (due_at) where due_at is not null;
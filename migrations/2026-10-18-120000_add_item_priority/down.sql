@@ -0,0 +1,5 @@
+drop index if exists todo_item_list_id_priority_idx;
+
+alter table todo_item drop column priority;
+
+drop type item_priority;
@@ -0,0 +1,6 @@
+-- Declaration order matters: enum values compare in this order when sorting.
+create type item_priority as enum ('none', 'low', 'medium', 'high', 'urgent');
+
+alter table todo_item add column priority item_priority not null default 'none';
+
+create index todo_item_list_id_priority_idx on todo_item (list_id, priority desc, due_at);
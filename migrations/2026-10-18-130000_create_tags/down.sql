@@ -0,0 +1,2 @@
+drop table if exists todo_item_tag;
+drop table if exists tag;
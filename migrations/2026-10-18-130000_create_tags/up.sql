@@ -0,0 +1,13 @@
+create table tag (
+    id serial primary key,
+    name varchar(50) not null unique
+);
+
+create table todo_item_tag (
+    item_id integer not null references todo_item(id) on delete cascade,
+    tag_id integer not null references tag(id) on delete cascade,
+    primary key (item_id, tag_id)
+);
+
+-- The primary key covers lookups by item; this one covers `?tag=` filtering.
+create index todo_item_tag_tag_id_idx on todo_item_tag (tag_id);
@@ -0,0 +1,5 @@
+drop index if exists todo_item_parent_id_idx;
+
+alter table todo_item drop constraint todo_item_parent_fkey;
+alter table todo_item drop constraint todo_item_list_id_id_key;
+alter table todo_item drop column parent_id;
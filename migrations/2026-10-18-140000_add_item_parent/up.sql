@@ -0,0 +1,11 @@
+alter table todo_item add column parent_id integer;
+
+-- Referencing (list_id, id) keeps children in their parent's list: deleting a parent removes its
+-- children and moving a parent to another list carries them along.
+alter table todo_item add constraint todo_item_list_id_id_key unique (list_id, id);
+alter table todo_item
+    add constraint todo_item_parent_fkey
+    foreign key (list_id, parent_id) references todo_item (list_id, id)
+    on update cascade on delete cascade;
+
+create index todo_item_parent_id_idx on todo_item (parent_id);
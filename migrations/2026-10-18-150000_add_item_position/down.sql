@@ -0,0 +1,2 @@
+alter table todo_item drop constraint todo_item_list_id_position_key;
+alter table todo_item drop column position;
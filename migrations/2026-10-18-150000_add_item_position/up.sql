@@ -0,0 +1,15 @@
+alter table todo_item add column position integer;
+
+-- Keep the current `order by id` ordering for existing items.
+update todo_item set position = ordered.position
+from (
+    select id, row_number() over (partition by list_id order by id) as position from todo_item
+) ordered
+where todo_item.id = ordered.id;
+
+alter table todo_item alter column position set not null;
+
+-- Deferrable so a renumbering UPDATE is only checked once the whole statement has run.
+alter table todo_item
+    add constraint todo_item_list_id_position_key unique (list_id, position)
+    deferrable initially immediate;
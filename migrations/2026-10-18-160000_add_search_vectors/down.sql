@@ -0,0 +1,5 @@
+drop index if exists todo_item_search_idx;
+drop index if exists todo_list_search_idx;
+
+alter table todo_item drop column search;
+alter table todo_list drop column search;
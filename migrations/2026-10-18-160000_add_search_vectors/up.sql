@@ -0,0 +1,11 @@
+-- Kept up to date by Postgres; `to_tsvector` needs an explicit configuration to be immutable.
+alter table todo_list
+    add column search tsvector
+    generated always as (to_tsvector('english', coalesce(title, ''))) stored;
+
+alter table todo_item
+    add column search tsvector
+    generated always as (to_tsvector('english', title)) stored;
+
+create index todo_list_search_idx on todo_list using gin (search);
+create index todo_item_search_idx on todo_item using gin (search);
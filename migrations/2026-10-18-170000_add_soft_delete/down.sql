@@ -0,0 +1,9 @@
+-- Trashed rows are deleted for good.
+delete from todo_list where deleted_at is not null;
+delete from todo_item where deleted_at is not null;
+
+drop index if exists todo_item_deleted_at_idx;
+drop index if exists todo_list_deleted_at_idx;
+
+alter table todo_item drop column deleted_at;
+alter table todo_list drop column deleted_at;
@@ -0,0 +1,7 @@
+-- Soft delete: rows stay in the trash until restored or purged. Items deleted on their own get a
+-- negative position (minus their id) so they free their slot in the list's ordering.
+alter table todo_list add column deleted_at timestamptz;
+alter table todo_item add column deleted_at timestamptz;
+
+create index todo_list_deleted_at_idx on todo_list (deleted_at) where deleted_at is not null;
+create index todo_item_deleted_at_idx on todo_item (deleted_at) where deleted_at is not null;
@@ -0,0 +1 @@
+alter table todo_list drop column archived;
@@ -0,0 +1,2 @@
+-- Archived lists are hidden from the default listing and read-only.
+alter table todo_list add column archived boolean not null default false;
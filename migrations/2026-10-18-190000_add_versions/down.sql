@@ -0,0 +1,6 @@
+drop trigger todo_item_bump_version on todo_item;
+drop trigger todo_list_bump_version on todo_list;
+drop function bump_version();
+
+alter table todo_item drop column version;
+alter table todo_list drop column version;
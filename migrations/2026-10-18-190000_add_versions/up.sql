@@ -0,0 +1,16 @@
+-- Row versions for optimistic concurrency (`ETag` / `If-Match`). Every update bumps the version,
+-- including ones made indirectly (renumbered positions, cascaded moves, checked sub-items).
+alter table todo_list add column version integer not null default 1;
+alter table todo_item add column version integer not null default 1;
+
+create function bump_version() returns trigger as $$
+begin
+    new.version := old.version + 1;
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger todo_list_bump_version before update on todo_list
+    for each row execute procedure bump_version();
+create trigger todo_item_bump_version before update on todo_item
+    for each row execute procedure bump_version();
@@ -0,0 +1,4 @@
+drop trigger todo_item_record_history on todo_item;
+drop trigger todo_list_record_history on todo_list;
+drop function record_history();
+drop table history;
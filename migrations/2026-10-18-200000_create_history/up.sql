@@ -0,0 +1,59 @@
+-- Change history for lists and items, recorded by triggers so every code path is covered. Each
+-- row keeps the values before and after the change; bookkeeping-only updates (renumbered
+-- positions, version bumps from tag changes) are not recorded.
+create table history (
+    id serial primary key,
+    list_id integer references todo_list (id) on delete cascade,
+    item_id integer references todo_item (id) on delete cascade,
+    action varchar(16) not null,
+    version integer not null,
+    old_values jsonb,
+    new_values jsonb not null,
+    changed_at timestamptz not null default now(),
+    check ((list_id is null) <> (item_id is null))
+);
+
+-- Versions only go up, so each one is recorded at most once per list or item.
+create unique index history_list_version_idx on history (list_id, version) where list_id is not null;
+create unique index history_item_version_idx on history (item_id, version) where item_id is not null;
+
+create function record_history() returns trigger as $$
+declare
+    ignored text[] := array['search', 'position', 'updated_at', 'version'];
+    old_values jsonb;
+    new_values jsonb := to_jsonb(new) - ignored;
+    action text := 'update';
+begin
+    if tg_op = 'INSERT' then
+        action := 'create';
+    else
+        old_values := to_jsonb(old) - ignored;
+
+        if old_values = new_values then
+            return null;
+        elsif old.deleted_at is null and new.deleted_at is not null then
+            action := 'delete';
+        elsif old.deleted_at is not null and new.deleted_at is null then
+            action := 'restore';
+        elsif old_values -> 'checked' is distinct from new_values -> 'checked' then
+            action := case when (new_values ->> 'checked')::boolean then 'check' else 'uncheck' end;
+        elsif old_values -> 'archived' is distinct from new_values -> 'archived' then
+            action := case when (new_values ->> 'archived')::boolean then 'archive' else 'unarchive' end;
+        end if;
+    end if;
+
+    insert into history (list_id, item_id, action, version, old_values, new_values)
+    values (
+        case when tg_table_name = 'todo_list' then new.id end,
+        case when tg_table_name = 'todo_item' then new.id end,
+        action, new.version, old_values, new_values
+    );
+
+    return null;
+end;
+$$ language plpgsql;
+
+create trigger todo_list_record_history after insert or update on todo_list
+    for each row execute procedure record_history();
+create trigger todo_item_record_history after insert or update on todo_item
+    for each row execute procedure record_history();
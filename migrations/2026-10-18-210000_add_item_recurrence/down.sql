@@ -0,0 +1 @@
+alter table todo_item drop column recurrence;
@@ -0,0 +1,3 @@
+-- Recurrence rule for chores and routines, e.g. `FREQ=WEEKLY;INTERVAL=2`. Checking a recurring
+-- item creates its next occurrence, which takes the rule over.
+alter table todo_item add column recurrence varchar(100);
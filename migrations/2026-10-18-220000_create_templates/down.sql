@@ -0,0 +1,2 @@
+drop table template_item;
+drop table template;
@@ -0,0 +1,18 @@
+-- Reusable list templates. Items are keyed by their position in the template, and sub-items
+-- point at their parent's position, so new lists can be filled with plain INSERT ... SELECTs.
+create table template (
+    id serial primary key,
+    name varchar(150) not null,
+    created_at timestamptz not null default now()
+);
+
+create table template_item (
+    template_id integer not null references template (id) on delete cascade,
+    position integer not null,
+    parent_position integer,
+    title varchar(150) not null,
+    priority item_priority not null default 'none',
+    recurrence varchar(100),
+    primary key (template_id, position),
+    foreign key (template_id, parent_position) references template_item (template_id, position)
+);
@@ -0,0 +1 @@
+alter table todo_item drop column previous_occurrence_id;
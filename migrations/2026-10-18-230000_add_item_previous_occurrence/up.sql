@@ -0,0 +1,7 @@
+-- Links each generated occurrence of a recurring item to the one whose check created it, so
+-- unchecking that one can take the occurrence back instead of leaving two copies of the chore.
+alter table todo_item add column previous_occurrence_id integer
+    references todo_item (id) on delete set null;
+
+create index todo_item_previous_occurrence_id_idx on todo_item (previous_occurrence_id)
+    where previous_occurrence_id is not null;
@@ -3,9 +3,6 @@
 pub use config::ConfigError;
 use serde::Deserialize;
 use slog::{o, Drain, Logger};
-use slog_async;
-use slog_envlogger;
-use slog_term;
 
 #[derive(Deserialize, Clone)]
 pub struct ServerConfig {
@@ -22,12 +19,56 @@ impl Default for ServerConfig {
     }
 }
 
+// How long deleted lists and items stay in the trash, and how often expired ones are purged.
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct TrashConfig {
+    pub retention_days: i64,
+    pub purge_interval_secs: u64,
+}
+
+// Upper bounds for the trash settings: a century of retention and a purge at least once a year.
+// Far larger values overflow the date and timer arithmetic of the purge task.
+const MAX_RETENTION_DAYS: i64 = 36_500;
+const MAX_PURGE_INTERVAL_SECS: u64 = 365 * 24 * 60 * 60;
+
+impl TrashConfig {
+    // Reject values the purge task can't work with: a zero interval makes the timer panic, a
+    // negative retention would purge everything in the trash right away, and huge values overflow.
+    fn validate(&self) -> Result<(), ConfigError> {
+        if !(1..=MAX_PURGE_INTERVAL_SECS).contains(&self.purge_interval_secs) {
+            return Err(ConfigError::Message(format!(
+                "trash.purge_interval_secs must be between 1 and {}",
+                MAX_PURGE_INTERVAL_SECS
+            )));
+        }
+        if !(0..=MAX_RETENTION_DAYS).contains(&self.retention_days) {
+            return Err(ConfigError::Message(format!(
+                "trash.retention_days must be between 0 and {}",
+                MAX_RETENTION_DAYS
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        TrashConfig {
+            retention_days: 30,
+            purge_interval_secs: 3600,
+        }
+    }
+}
+
 #[derive(Deserialize, Clone)]
 pub struct Config {
     #[serde(default)]
     pub server: ServerConfig,
     #[serde(default)]
     pub pg: deadpool_postgres::Config,
+    #[serde(default)]
+    pub trash: TrashConfig,
 }
 
 impl Config {
@@ -36,7 +77,9 @@ impl Config {
         let cfg = config::Config::builder()
             .add_source(config::Environment::default().separator("."))
             .build()?;
-        cfg.try_deserialize()
+        let config: Config = cfg.try_deserialize()?;
+        config.trash.validate()?;
+        Ok(config)
     }
 
     pub fn configure_log() -> Logger {
@@ -48,3 +91,59 @@ impl Config {
         slog::Logger::root(console_drain, o!("v" => env!("CARGO_PKG_VERSION")))
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::TrashConfig;
+
+    #[test]
+    fn test_trash_config_validation() {
+        assert!(
+            TrashConfig::default().validate().is_ok(),
+            "Defaults are valid"
+        );
+
+        let zero_interval = TrashConfig {
+            purge_interval_secs: 0,
+            ..TrashConfig::default()
+        };
+        assert!(
+            zero_interval.validate().is_err(),
+            "The purge interval must be non-zero"
+        );
+
+        let negative_retention = TrashConfig {
+            retention_days: -1,
+            ..TrashConfig::default()
+        };
+        assert!(
+            negative_retention.validate().is_err(),
+            "Retention can't be negative"
+        );
+
+        let huge_retention = TrashConfig {
+            retention_days: 999_999_999_999,
+            ..TrashConfig::default()
+        };
+        assert!(
+            huge_retention.validate().is_err(),
+            "Retention must fit in a date"
+        );
+
+        let huge_interval = TrashConfig {
+            purge_interval_secs: u64::MAX,
+            ..TrashConfig::default()
+        };
+        assert!(
+            huge_interval.validate().is_err(),
+            "The purge interval must fit in the timer"
+        );
+
+        let no_retention = TrashConfig {
+            retention_days: 0,
+            ..TrashConfig::default()
+        };
+        assert!(no_retention.validate().is_ok(), "Zero retention is allowed");
+    }
+}
@@ -1,14 +1,30 @@
 // File: src/db.rs
 // High-level: Data-access layer. Each function encapsulates a single SQL statement and maps rows to typed models.
 use crate::errors::{AppError, AppErrorType::*};
-use crate::models::{TodoItem, TodoList};
-use deadpool_postgres::Client;
+use crate::etag::IfMatch;
+use crate::models::{
+    BulkOperation, BulkOperationResult, CreateTodoItem, DuplicateTodoList, DuplicatedTodoList,
+    HistoryEntry, InstantiateTemplate, ItemFilter, ItemSort, ListFilter, Priority, Recurrence,
+    ReorderItems, SearchHit, SearchQuery, Tag, Template, TodoItem, TodoList, TodoListSummary,
+    Trash, UpdateTodoItem,
+};
+use crate::pagination::{Cursor, Page, Pagination, DEFAULT_LIMIT, MAX_LIMIT};
+use chrono::{DateTime, Utc};
+use deadpool_postgres::{Client, Transaction};
 use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Row;
+
+// Columns selected (or returned) for every `TodoItem`: the row itself plus its tags as a `tag[]`.
+const ITEM_COLUMNS: &str = "todo_item.*, array(\
+     select tag from tag join todo_item_tag on todo_item_tag.tag_id = tag.id \
+     where todo_item_tag.item_id = todo_item.id order by tag.name) as tags";
 
 // Insert a new todo list and return the created row for immediate client feedback.
 pub async fn create_todo(client: &Client, title: &str) -> Result<TodoList, AppError> {
     let statement = client
-        .prepare("insert into todo_list (title) values ($1) returning id, title")
+        .prepare("insert into todo_list (title) values ($1) returning *")
         .await?;
 
     client
@@ -25,55 +41,537 @@ pub async fn create_todo(client: &Client, title: &str) -> Result<TodoList, AppEr
         })
 }
 
-// Fetch all todo lists ordered by newest first to surface recent lists.
-pub async fn get_todos(client: &Client) -> Result<Vec<TodoList>, AppError> {
+// Sort direction of a keyset column.
+#[derive(Clone, Copy)]
+enum Direction {
+    Asc,
+    Desc,
+}
+
+// A named, total ordering used for keyset pagination. Each key is a non-null SQL expression,
+// its direction and its SQL type; the last key must be unique (the id).
+struct Keyset {
+    name: &'static str,
+    keys: &'static [(&'static str, Direction, &'static str)],
+}
+
+// Todo lists, newest first.
+const LIST_KEYSET: Keyset = Keyset {
+    name: "newest",
+    keys: &[("todo_list.id", Direction::Desc, "int4")],
+};
+
+// Items in their list order.
+const POSITION_KEYSET: Keyset = Keyset {
+    name: "position",
+    keys: &[
+        ("todo_item.position", Direction::Asc, "int4"),
+        ("todo_item.id", Direction::Asc, "int4"),
+    ],
+};
+
+// Items alphabetically by title.
+const TITLE_KEYSET: Keyset = Keyset {
+    name: "title",
+    keys: &[
+        ("todo_item.title", Direction::Asc, "text"),
+        ("todo_item.id", Direction::Asc, "int4"),
+    ],
+};
+
+// Items newest first.
+const NEWEST_KEYSET: Keyset = Keyset {
+    name: "-created",
+    keys: &[
+        ("todo_item.created_at", Direction::Desc, "timestamptz"),
+        ("todo_item.id", Direction::Desc, "int4"),
+    ],
+};
+
+// Items by priority, then soonest due date (undated last), then list order.
+const PRIORITY_KEYSET: Keyset = Keyset {
+    name: "priority",
+    keys: &[
+        ("todo_item.priority", Direction::Desc, "item_priority"),
+        (
+            "coalesce(todo_item.due_at, 'infinity')",
+            Direction::Asc,
+            "timestamptz",
+        ),
+        ("todo_item.position", Direction::Asc, "int4"),
+        ("todo_item.id", Direction::Asc, "int4"),
+    ],
+};
+
+impl Keyset {
+    fn order_sql(&self) -> String {
+        self.keys
+            .iter()
+            .map(|(expr, direction, _)| match direction {
+                Direction::Asc => format!("{} asc", expr),
+                Direction::Desc => format!("{} desc", expr),
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    // Extra column holding each row's sort key as text, used to build the next cursor.
+    fn cursor_column(&self) -> String {
+        let keys = self
+            .keys
+            .iter()
+            .map(|(expr, _, _)| format!("({})::text", expr))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!("array[{}] as cursor_key", keys)
+    }
+
+    // Predicate selecting the rows after `cursor`, binding its key values to `$first` onwards.
+    // Without a cursor it matches every row.
+    fn after_sql(&self, cursor: Option<&Cursor>, first: usize) -> Result<String, AppError> {
+        let cursor = match cursor {
+            None => return Ok("true".to_string()),
+            Some(cursor) => cursor,
+        };
+
+        if cursor.order != self.name || cursor.key.len() != self.keys.len() {
+            return Err(AppError {
+                message: Some("Cursor does not match the requested ordering.".to_string()),
+                cause: None,
+                error_type: BadRequestError,
+            });
+        }
+
+        // The values are cast in SQL, where a tampered key would fail the whole query.
+        let valid = self
+            .keys
+            .iter()
+            .zip(&cursor.key)
+            .all(|((_, _, sql_type), value)| valid_key(sql_type, value));
+        if !valid {
+            return Err(AppError {
+                message: Some("Invalid cursor.".to_string()),
+                cause: None,
+                error_type: BadRequestError,
+            });
+        }
+
+        let values = self
+            .keys
+            .iter()
+            .enumerate()
+            .map(|(i, (expr, direction, sql_type))| {
+                let operator = match direction {
+                    Direction::Asc => ">",
+                    Direction::Desc => "<",
+                };
+                (
+                    expr,
+                    operator,
+                    format!("${}::text::{}", first + i, sql_type),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let branches = (0..values.len())
+            .map(|i| {
+                let mut terms = values[..i]
+                    .iter()
+                    .map(|(expr, _, value)| format!("{} = {}", expr, value))
+                    .collect::<Vec<String>>();
+                let (expr, operator, value) = &values[i];
+                terms.push(format!("{} {} {}", expr, operator, value));
+                format!("({})", terms.join(" and "))
+            })
+            .collect::<Vec<String>>();
+
+        Ok(format!("({})", branches.join(" or ")))
+    }
+
+    // Keep the first `limit` rows of a `limit + 1` query; the extra row means there is a next page.
+    fn page<T>(&self, rows: Vec<Row>, limit: i64, map: impl Fn(&Row) -> T) -> Page<T> {
+        let limit = limit as usize;
+        let next_cursor = (rows.len() > limit).then(|| {
+            Cursor {
+                order: self.name.to_string(),
+                key: rows[limit - 1].get("cursor_key"),
+            }
+            .encode()
+        });
+
+        Page {
+            items: rows.iter().take(limit).map(map).collect(),
+            next_cursor,
+        }
+    }
+}
+
+// Whether a cursor key value, as produced by `cursor_column`, can be cast back to its SQL type.
+fn valid_key(sql_type: &str, value: &str) -> bool {
+    match sql_type {
+        "int4" => value.parse::<i32>().is_ok(),
+        "timestamptz" => {
+            value == "infinity"
+                || DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f%#z").is_ok()
+        }
+        "item_priority" => serde_json::from_value::<Priority>(value.into()).is_ok(),
+        _ => true,
+    }
+}
+
+// Cursor key values as query parameters, to follow the statement's own parameters.
+fn cursor_params(pagination: &Pagination) -> Vec<&(dyn ToSql + Sync)> {
+    pagination
+        .cursor
+        .iter()
+        .flat_map(|cursor| cursor.key.iter())
+        .map(|value| value as &(dyn ToSql + Sync))
+        .collect()
+}
+
+// Selected for every `TodoListSummary`: the list row plus counts of its items. Needs
+// `LIST_SUMMARY_JOIN` and `group by todo_list.id`, so each list takes a single grouped query.
+const LIST_SUMMARY_COLUMNS: &str = "todo_list.*, count(todo_item.id) as item_count, \
+     count(todo_item.id) filter (where todo_item.checked) as checked_count";
+const LIST_SUMMARY_JOIN: &str = "todo_list left join todo_item \
+     on todo_item.list_id = todo_list.id and todo_item.deleted_at is null";
+
+fn list_summary(row: &Row) -> TodoListSummary {
+    TodoListSummary::new(
+        TodoList::from_row_ref(row).unwrap(),
+        row.get("item_count"),
+        row.get("checked_count"),
+    )
+}
+
+// Fetch a page of todo lists ordered by newest first to surface recent lists. Archived lists are
+// only returned, on their own, with `archived: Some(true)`.
+pub async fn get_todos(
+    client: &Client,
+    filter: &ListFilter,
+    pagination: &Pagination,
+) -> Result<Page<TodoListSummary>, AppError> {
+    let keyset = &LIST_KEYSET;
     let statement = client
-        .prepare("select * from todo_list order by id desc")
+        .prepare(&format!(
+            "select {}, {} from {} \
+             where todo_list.deleted_at is null and todo_list.archived = $2 and {} \
+             group by todo_list.id order by {} limit $1",
+            LIST_SUMMARY_COLUMNS,
+            keyset.cursor_column(),
+            LIST_SUMMARY_JOIN,
+            keyset.after_sql(pagination.cursor.as_ref(), 3)?,
+            keyset.order_sql()
+        ))
         .await?;
 
-    let todos = client
-        .query(&statement, &[])
-        .await?
-        .iter()
-        .map(|row| TodoList::from_row_ref(row).unwrap())
-        .collect::<Vec<TodoList>>();
+    let limit = pagination.limit + 1;
+    let archived = filter.archived.unwrap_or(false);
+    let mut params: Vec<&(dyn ToSql + Sync)> = vec![&limit, &archived];
+    params.extend(cursor_params(pagination));
+
+    let rows = client.query(&statement, &params).await?;
 
-    Ok(todos)
+    Ok(keyset.page(rows, pagination.limit, list_summary))
 }
 
-// Fetch a single todo list by id or return a 404-style domain error.
-pub async fn get_todo(client: &Client, list_id: i32) -> Result<TodoList, AppError> {
+// Fetch a single todo list by id, with its item counts, or return a 404-style domain error.
+pub async fn get_todo(client: &Client, list_id: i32) -> Result<TodoListSummary, AppError> {
     let statement = client
-        .prepare("select * from todo_list where id = $1")
+        .prepare(&format!(
+            "select {} from {} where todo_list.id = $1 and todo_list.deleted_at is null \
+             group by todo_list.id",
+            LIST_SUMMARY_COLUMNS, LIST_SUMMARY_JOIN
+        ))
         .await?;
 
     let maybe_todo = client
         .query_opt(&statement, &[&list_id])
         .await?
-        .map(|row| TodoList::from_row_ref(&row).unwrap());
+        .map(|row| list_summary(&row));
 
     match maybe_todo {
         Some(todo) => Ok(todo),
-        None => Err(AppError {
-            error_type: NotFoundError,
+        None => Err(list_not_found(list_id)),
+    }
+}
+
+// Apply a partial update to a todo list; fields left as `None` keep their current value.
+pub async fn update_todo(
+    client: &mut Client,
+    list_id: i32,
+    title: Option<&str>,
+    if_match: &IfMatch,
+) -> Result<TodoList, AppError> {
+    let transaction = client.transaction().await?;
+
+    if_match.check(lock_list(&transaction, list_id).await?)?;
+
+    let statement = transaction
+        .prepare(
+            "update todo_list set title = coalesce($2, title), updated_at = now() \
+             where id = $1 returning *",
+        )
+        .await?;
+
+    let todo = transaction
+        .query_one(&statement, &[&list_id, &title])
+        .await
+        .map(|row| TodoList::from_row_ref(&row).unwrap())?;
+
+    transaction.commit().await?;
+
+    Ok(todo)
+}
+
+// Move a todo list together with all of its items to the trash atomically. The items share the
+// list's `deleted_at`, which is how restoring the list finds them again.
+pub async fn delete_todo(
+    client: &mut Client,
+    list_id: i32,
+    if_match: &IfMatch,
+) -> Result<(), AppError> {
+    let transaction = client.transaction().await?;
+
+    if_match.check(list_version(&transaction, list_id).await?)?;
+
+    let delete_items = transaction
+        .prepare(
+            "update todo_item set deleted_at = now() where list_id = $1 and deleted_at is null",
+        )
+        .await?;
+    let delete_list = transaction
+        .prepare("update todo_list set deleted_at = now() where id = $1 and deleted_at is null")
+        .await?;
+
+    transaction.execute(&delete_items, &[&list_id]).await?;
+    let deleted = transaction.execute(&delete_list, &[&list_id]).await?;
+
+    // Dropping the transaction without committing rolls back the item deletion.
+    if deleted == 0 {
+        return Err(list_not_found(list_id));
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+// Archive or unarchive a list and return it.
+pub async fn archive_todo(
+    client: &mut Client,
+    list_id: i32,
+    archived: bool,
+    if_match: &IfMatch,
+) -> Result<TodoList, AppError> {
+    let transaction = client.transaction().await?;
+
+    if_match.check(list_version(&transaction, list_id).await?)?;
+
+    let statement = transaction
+        .prepare("update todo_list set archived = $2, updated_at = now() where id = $1 returning *")
+        .await?;
+
+    let todo = transaction
+        .query_one(&statement, &[&list_id, &archived])
+        .await
+        .map(|row| TodoList::from_row_ref(&row).unwrap())?;
+
+    transaction.commit().await?;
+
+    Ok(todo)
+}
+
+// Copy a list and its items (not the trashed ones), keeping their order, nesting and tags, in one
+// transaction. `reset_checked` unchecks every copy. Returns the new list and how many items were
+// copied.
+pub async fn duplicate_todo(
+    client: &mut Client,
+    list_id: i32,
+    duplicate: &DuplicateTodoList,
+) -> Result<DuplicatedTodoList, AppError> {
+    let transaction = client.transaction().await?;
+
+    let statement = transaction
+        .prepare(
+            "insert into todo_list (title) \
+             select coalesce($2, title) from todo_list where id = $1 and deleted_at is null \
+             returning *",
+        )
+        .await?;
+
+    let list = transaction
+        .query_opt(&statement, &[&list_id, &duplicate.title])
+        .await?
+        .map(|row| TodoList::from_row_ref(&row).unwrap())
+        .ok_or_else(|| list_not_found(list_id))?;
+
+    // Ids are drawn up front so sub-items and tags can point at the copies in the same statement.
+    let statement = transaction
+        .prepare(
+            "with new_id as ( \
+                 select id as old_id, \
+                     nextval(pg_get_serial_sequence('todo_item', 'id'))::int as id \
+                 from todo_item where list_id = $1 and deleted_at is null \
+             ), copied as ( \
+                 insert into todo_item (id, list_id, parent_id, title, checked, completed_at, \
+                     due_at, priority, recurrence, position) \
+                 select new_id.id, $2, parent.id, item.title, item.checked and not $3, \
+                     case when not $3 then item.completed_at end, item.due_at, item.priority, \
+                     item.recurrence, item.position \
+                 from todo_item item \
+                 join new_id on new_id.old_id = item.id \
+                 left join new_id parent on parent.old_id = item.parent_id \
+                 returning id \
+             ), tagged as ( \
+                 insert into todo_item_tag (item_id, tag_id) \
+                 select new_id.id, tag_id from todo_item_tag \
+                 join new_id on new_id.old_id = todo_item_tag.item_id \
+             ) \
+             select count(*) from copied",
+        )
+        .await?;
+
+    let item_count: i64 = transaction
+        .query_one(&statement, &[&list_id, &list.id, &duplicate.reset_checked])
+        .await?
+        .get(0);
+
+    transaction.commit().await?;
+
+    Ok(DuplicatedTodoList { list, item_count })
+}
+
+// Lock a list row, whether archived or not, and return its version.
+async fn list_version(transaction: &Transaction<'_>, list_id: i32) -> Result<i32, AppError> {
+    let statement = transaction
+        .prepare("select version from todo_list where id = $1 and deleted_at is null for update")
+        .await?;
+
+    transaction
+        .query_opt(&statement, &[&list_id])
+        .await?
+        .map(|row| row.get(0))
+        .ok_or_else(|| list_not_found(list_id))
+}
+
+// Lock a list row for the rest of the transaction so concurrent writers renumbering its item
+// positions are serialized, and return its version. Returns a not-found error if the list does not
+// exist or is in the trash, and a client error if it is archived.
+async fn lock_list(transaction: &Transaction<'_>, list_id: i32) -> Result<i32, AppError> {
+    let statement = transaction
+        .prepare(
+            "select archived, version from todo_list where id = $1 and deleted_at is null \
+             for update",
+        )
+        .await?;
+
+    match transaction
+        .query_opt(&statement, &[&list_id])
+        .await?
+        .map(|row| (row.get(0), row.get(1)))
+    {
+        None => Err(list_not_found(list_id)),
+        Some((true, _)) => Err(AppError {
+            error_type: BadRequestError,
             cause: None,
-            message: Some(format!("Todo list {} not found.", list_id)),
+            message: Some(format!(
+                "Todo list {} is archived and read-only. Unarchive it first.",
+                list_id
+            )),
         }),
+        Some((false, version)) => Ok(version),
     }
 }
 
-// Insert a new item in the specified list and return the created row.
+// Lock an item row for the rest of the transaction and return its version, or a not-found error.
+async fn lock_item(
+    transaction: &Transaction<'_>,
+    list_id: i32,
+    item_id: i32,
+) -> Result<i32, AppError> {
+    let statement = transaction
+        .prepare(
+            "select version from todo_item where list_id = $1 and id = $2 and deleted_at is null \
+             for update",
+        )
+        .await?;
+
+    transaction
+        .query_opt(&statement, &[&list_id, &item_id])
+        .await?
+        .map(|row| row.get(0))
+        .ok_or_else(|| item_not_found(list_id, item_id))
+}
+
+// Close any gaps left in a list's item positions, keeping their relative order. Trashed items
+// keep their negative positions.
+async fn renumber_positions(transaction: &Transaction<'_>, list_id: i32) -> Result<u64, AppError> {
+    let statement = transaction
+        .prepare(
+            "update todo_item set position = ordered.position \
+             from (select id, row_number() over (order by position, id)::int as position \
+                   from todo_item where list_id = $1 and deleted_at is null) ordered \
+             where todo_item.id = ordered.id and todo_item.position <> ordered.position",
+        )
+        .await?;
+
+    Ok(transaction.execute(&statement, &[&list_id]).await?)
+}
+
+// Insert a new item at the end of the specified list, optionally as a child of `parent_id`,
+// and return the created row.
 pub async fn create_item(
-    client: &Client,
+    client: &mut Client,
     list_id: i32,
-    title: &str,
+    parent_id: Option<i32>,
+    item: &CreateTodoItem,
 ) -> Result<TodoItem, AppError> {
-    let statement = client
-        .prepare("insert into todo_item (list_id, title) values ($1, $2) returning id, list_id, title, checked")
+    let transaction = client.transaction().await?;
+
+    lock_list(&transaction, list_id).await?;
+
+    if let Some(parent_id) = parent_id {
+        let statement = transaction
+            .prepare(
+                "select id from todo_item where list_id = $1 and id = $2 and deleted_at is null",
+            )
+            .await?;
+
+        if transaction
+            .query_opt(&statement, &[&list_id, &parent_id])
+            .await?
+            .is_none()
+        {
+            return Err(item_not_found(list_id, parent_id));
+        }
+    }
+
+    let statement = transaction
+        .prepare(&format!(
+            "insert into todo_item (list_id, parent_id, title, due_at, priority, recurrence, \
+                 position) \
+             values ($1, $2, $3, $4, $5, $6, \
+                 (select coalesce(max(position), 0) + 1 from todo_item \
+                  where list_id = $1 and deleted_at is null)) \
+             returning {}",
+            ITEM_COLUMNS
+        ))
         .await?;
 
-    client
-        .query(&statement, &[&list_id, &title])
+    let item = transaction
+        .query(
+            &statement,
+            &[
+                &list_id,
+                &parent_id,
+                &item.title,
+                &item.due_at,
+                &item.priority,
+                &item.recurrence,
+            ],
+        )
         .await?
         .iter()
         .map(|row| TodoItem::from_row_ref(row).unwrap())
@@ -83,17 +581,378 @@ pub async fn create_item(
             message: Some("Error creating TODO item".to_string()),
             cause: Some("Unknown error".to_string()),
             error_type: DbError,
-        })
+        })?;
+
+    transaction.commit().await?;
+
+    Ok(item)
+}
+
+// Move an item, together with its sub-items, to the end of another list and return it.
+// A sub-item moved on its own becomes a top-level item of the target list. Data keyed by item id
+// (such as tags) stays attached to the moved items.
+pub async fn move_item(
+    client: &mut Client,
+    list_id: i32,
+    item_id: i32,
+    target_list_id: i32,
+    if_match: &IfMatch,
+) -> Result<TodoItem, AppError> {
+    if target_list_id == list_id {
+        let item = get_item(client, list_id, item_id).await?;
+        if_match.check(item.version)?;
+        return Ok(item);
+    }
+
+    let transaction = client.transaction().await?;
+
+    // Lock both lists in a fixed order so two opposite moves cannot deadlock.
+    for id in [list_id.min(target_list_id), list_id.max(target_list_id)] {
+        lock_list(&transaction, id).await?;
+    }
+    if_match.check(lock_item(&transaction, list_id, item_id).await?)?;
+
+    // Positions are renumbered below; only check their uniqueness once everything has moved.
+    transaction
+        .batch_execute("set constraints todo_item_list_id_position_key deferred")
+        .await?;
+
+    let statement = transaction
+        .prepare(
+            "with recursive subtree as ( \
+                 select id from todo_item where list_id = $1 and id = $2 and deleted_at is null \
+                 union all \
+                 select todo_item.id from todo_item join subtree on todo_item.parent_id = subtree.id \
+                 where todo_item.deleted_at is null \
+             ) \
+             select id from subtree",
+        )
+        .await?;
+
+    let subtree = transaction
+        .query(&statement, &[&list_id, &item_id])
+        .await?
+        .iter()
+        .map(|row| row.get::<_, i32>(0))
+        .collect::<Vec<i32>>();
+
+    if subtree.is_empty() {
+        return Err(item_not_found(list_id, item_id));
+    }
+
+    // Sub-items, including trashed ones, follow through the `on update cascade` parent foreign key.
+    let statement = transaction
+        .prepare(
+            "update todo_item set list_id = $3, parent_id = null, updated_at = now() \
+             where list_id = $1 and id = $2",
+        )
+        .await?;
+
+    transaction
+        .execute(&statement, &[&list_id, &item_id, &target_list_id])
+        .await?;
+
+    let statement = transaction
+        .prepare(
+            "update todo_item set position = moved.position \
+             from (select id, (row_number() over (order by position, id) + \
+                       (select coalesce(max(position), 0) from todo_item \
+                        where list_id = $1 and id <> all($2) and deleted_at is null))::int as position \
+                   from todo_item where id = any($2)) moved \
+             where todo_item.id = moved.id",
+        )
+        .await?;
+
+    transaction
+        .execute(&statement, &[&target_list_id, &subtree])
+        .await?;
+
+    renumber_positions(&transaction, list_id).await?;
+
+    let statement = transaction
+        .prepare(&format!(
+            "select {} from todo_item where list_id = $1 and id = $2",
+            ITEM_COLUMNS
+        ))
+        .await?;
+
+    let item = transaction
+        .query_one(&statement, &[&target_list_id, &item_id])
+        .await
+        .map(|row| TodoItem::from_row_ref(&row).unwrap())?;
+
+    transaction.commit().await?;
+
+    Ok(item)
+}
+
+// Rearrange a list's items, either by moving one item next to another or from a full ordering,
+// and return the items in their new order. Positions stay dense (1..n) and unique. A move fails
+// with 412 if the moved item is no longer at the given version.
+pub async fn reorder_items(
+    client: &mut Client,
+    list_id: i32,
+    reorder: &ReorderItems,
+) -> Result<Vec<TodoItem>, AppError> {
+    let transaction = client.transaction().await?;
+
+    lock_list(&transaction, list_id).await?;
+
+    if let ReorderItems::Move {
+        item_id,
+        version: Some(version),
+        ..
+    } = reorder
+    {
+        IfMatch::from_version(Some(*version))
+            .check(lock_item(&transaction, list_id, *item_id).await?)?;
+    }
+
+    let statement = transaction
+        .prepare(
+            "select id from todo_item where list_id = $1 and deleted_at is null order by position, id",
+        )
+        .await?;
+
+    let current = transaction
+        .query(&statement, &[&list_id])
+        .await?
+        .iter()
+        .map(|row| row.get::<_, i32>(0))
+        .collect::<Vec<i32>>();
+
+    let order = reorder.apply(&current).map_err(|message| AppError {
+        error_type: BadRequestError,
+        cause: None,
+        message: Some(message),
+    })?;
+
+    let statement = transaction
+        .prepare(
+            "update todo_item set position = ordered.position, updated_at = now() \
+             from unnest($2::int[]) with ordinality as ordered(id, position) \
+             where todo_item.list_id = $1 and todo_item.id = ordered.id \
+             and todo_item.position <> ordered.position",
+        )
+        .await?;
+
+    transaction.execute(&statement, &[&list_id, &order]).await?;
+
+    let statement = transaction
+        .prepare(&format!(
+            "select {} from todo_item where list_id = $1 and deleted_at is null order by position, id",
+            ITEM_COLUMNS
+        ))
+        .await?;
+
+    let items = transaction
+        .query(&statement, &[&list_id])
+        .await?
+        .iter()
+        .map(|row| TodoItem::from_row_ref(row).unwrap())
+        .collect::<Vec<TodoItem>>();
+
+    transaction.commit().await?;
+
+    Ok(items)
+}
+
+// SQL predicates for an `ItemFilter`, binding its values (see `item_filter_params`) to `$first`
+// onwards. Only the parameter numbers are formatted in; every value is a bind parameter.
+// An item is overdue when its due date has passed and it is still unchecked.
+fn item_filter_sql(first: usize) -> String {
+    format!(
+        "(${0}::timestamptz is null or due_at < ${0}) \
+         and (${1}::bool is null or (coalesce(due_at < now(), false) and not checked) = ${1}) \
+         and (${2}::text is null or exists (\
+             select 1 from todo_item_tag join tag on tag.id = todo_item_tag.tag_id \
+             where todo_item_tag.item_id = todo_item.id and tag.name = ${2})) \
+         and (${3}::bool is null or checked = ${3}) \
+         and (${4}::text is null or strpos(lower(title), lower(${4})) > 0)",
+        first,
+        first + 1,
+        first + 2,
+        first + 3,
+        first + 4
+    )
+}
+
+// Values bound by `item_filter_sql`, in order.
+fn item_filter_params(filter: &ItemFilter) -> Vec<&(dyn ToSql + Sync)> {
+    vec![
+        &filter.due_before,
+        &filter.overdue,
+        &filter.tag,
+        &filter.checked,
+        &filter.q,
+    ]
+}
+
+// Ordering for an item listing.
+fn item_keyset(sort: Option<ItemSort>) -> &'static Keyset {
+    match sort {
+        None | Some(ItemSort::Position) => &POSITION_KEYSET,
+        Some(ItemSort::Priority) => &PRIORITY_KEYSET,
+        Some(ItemSort::Title) => &TITLE_KEYSET,
+        Some(ItemSort::Created) => &NEWEST_KEYSET,
+    }
+}
+
+// List a page of items for a list so the client can render them, optionally filtered and sorted.
+pub async fn get_items(
+    client: &Client,
+    list_id: i32,
+    filter: &ItemFilter,
+    pagination: &Pagination,
+) -> Result<Page<TodoItem>, AppError> {
+    let keyset = item_keyset(filter.sort);
+    let statement = client
+        .prepare(&format!(
+            "select {}, {} from todo_item where list_id = $1 and deleted_at is null and {} and {} \
+             order by {} limit $2",
+            ITEM_COLUMNS,
+            keyset.cursor_column(),
+            item_filter_sql(3),
+            keyset.after_sql(pagination.cursor.as_ref(), 8)?,
+            keyset.order_sql()
+        ))
+        .await?;
+
+    let limit = pagination.limit + 1;
+    let mut params: Vec<&(dyn ToSql + Sync)> = vec![&list_id, &limit];
+    params.extend(item_filter_params(filter));
+    params.extend(cursor_params(pagination));
+
+    let rows = client.query(&statement, &params).await?;
+
+    Ok(keyset.page(rows, pagination.limit, |row| {
+        TodoItem::from_row_ref(row).unwrap()
+    }))
 }
 
-// List items for a list so the client can render them.
-pub async fn get_items(client: &Client, list_id: i32) -> Result<Vec<TodoItem>, AppError> {
+// Search items across all lists with the same filters and ordering as `get_items`.
+pub async fn search_items(client: &Client, filter: &ItemFilter) -> Result<Vec<TodoItem>, AppError> {
     let statement = client
-        .prepare("select * from todo_item where list_id = $1 order by id")
+        .prepare(&format!(
+            "select {} from todo_item where deleted_at is null and {} order by {}",
+            ITEM_COLUMNS,
+            item_filter_sql(1),
+            item_keyset(filter.sort).order_sql()
+        ))
         .await?;
 
     let items = client
-        .query(&statement, &[&list_id])
+        .query(&statement, &item_filter_params(filter))
+        .await?
+        .iter()
+        .map(|row| TodoItem::from_row_ref(row).unwrap())
+        .collect::<Vec<TodoItem>>();
+
+    Ok(items)
+}
+
+// Full-text search over list and item titles, best matches first. `q` uses web search syntax
+// (quoted phrases, `or`, `-word`).
+pub async fn search(client: &Client, query: &SearchQuery) -> Result<Vec<SearchHit>, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    let invalid = if query.q.trim().is_empty() {
+        Some("'q' cannot be empty.".to_string())
+    } else if !(1..=MAX_LIMIT).contains(&limit) {
+        Some(format!("'limit' must be between 1 and {}.", MAX_LIMIT))
+    } else {
+        None
+    };
+    if let Some(message) = invalid {
+        return Err(AppError {
+            message: Some(message),
+            cause: None,
+            error_type: BadRequestError,
+        });
+    }
+
+    let statement = client
+        .prepare(&format!(
+            "with search as (select websearch_to_tsquery('english', $1) as query) \
+             select 'list' as kind, todo_list.id as list_id, todo_list.title as list_title, \
+                 null::int4 as item_id, todo_list.title, {} as snippet, \
+                 ts_rank(todo_list.search, query) as rank \
+             from todo_list, search where todo_list.search @@ query and todo_list.deleted_at is null \
+             union all \
+             select 'item', todo_item.list_id, todo_list.title, todo_item.id, todo_item.title, {}, \
+                 ts_rank(todo_item.search, query) \
+             from todo_item join todo_list on todo_list.id = todo_item.list_id, search \
+             where todo_item.search @@ query and todo_item.deleted_at is null \
+             order by rank desc, list_id, item_id nulls first \
+             limit $2",
+            headline("coalesce(todo_list.title, '')"),
+            headline("todo_item.title")
+        ))
+        .await?;
+
+    let hits = client
+        .query(&statement, &[&query.q, &limit])
+        .await?
+        .iter()
+        .map(|row| SearchHit::from_row_ref(row).unwrap())
+        .collect::<Vec<SearchHit>>();
+
+    Ok(hits)
+}
+
+// SQL for a search snippet of `title`: the title is HTML-escaped first, so the `<b>` tags around
+// the matching terms are the only markup clients get.
+fn headline(title: &str) -> String {
+    format!(
+        "ts_headline('english', \
+             replace(replace(replace(replace(replace({}, '&', '&amp;'), '<', '&lt;'), '>', '&gt;'), \
+                 '\"', '&quot;'), '''', '&#39;'), \
+             query, 'StartSel=<b>, StopSel=</b>')",
+        title
+    )
+}
+
+// List items with a due date across all lists, soonest first.
+pub async fn get_due_items(
+    client: &Client,
+    filter: &ItemFilter,
+) -> Result<Vec<TodoItem>, AppError> {
+    let statement = client
+        .prepare(&format!(
+            "select {} from todo_item where due_at is not null and deleted_at is null and {} \
+             order by due_at, id",
+            ITEM_COLUMNS,
+            item_filter_sql(1)
+        ))
+        .await?;
+
+    let items = client
+        .query(&statement, &item_filter_params(filter))
+        .await?
+        .iter()
+        .map(|row| TodoItem::from_row_ref(row).unwrap())
+        .collect::<Vec<TodoItem>>();
+
+    Ok(items)
+}
+
+// List the direct children of an item.
+pub async fn get_children(
+    client: &Client,
+    list_id: i32,
+    item_id: i32,
+) -> Result<Vec<TodoItem>, AppError> {
+    get_item(client, list_id, item_id).await?;
+
+    let statement = client
+        .prepare(&format!(
+            "select {} from todo_item where list_id = $1 and parent_id = $2 and deleted_at is null \
+             order by position, id",
+            ITEM_COLUMNS
+        ))
+        .await?;
+
+    let items = client
+        .query(&statement, &[&list_id, &item_id])
         .await?
         .iter()
         .map(|row| TodoItem::from_row_ref(row).unwrap())
@@ -105,7 +964,10 @@ pub async fn get_items(client: &Client, list_id: i32) -> Result<Vec<TodoItem>, A
 // Fetch a specific item or return a not-found domain error.
 pub async fn get_item(client: &Client, list_id: i32, item_id: i32) -> Result<TodoItem, AppError> {
     let statement = client
-        .prepare("select * from todo_item where list_id = $1 and id = $2")
+        .prepare(&format!(
+            "select {} from todo_item where list_id = $1 and id = $2 and deleted_at is null",
+            ITEM_COLUMNS
+        ))
         .await?;
 
     let maybe_item = client
@@ -115,29 +977,1078 @@ pub async fn get_item(client: &Client, list_id: i32, item_id: i32) -> Result<Tod
 
     match maybe_item {
         Some(item) => Ok(item),
-        None => Err(AppError {
-            error_type: NotFoundError,
-            cause: None,
-            message: Some(format!(
-                "Todo item {} from list {} not found.",
-                item_id, list_id
-            )),
-        }),
+        None => Err(item_not_found(list_id, item_id)),
     }
 }
 
-// Mark an item as checked; returns whether an update occurred to enable idempotent behavior.
-pub async fn check_todo(client: &Client, list_id: i32, item_id: i32) -> Result<bool, AppError> {
-    let statement = client
-        .prepare("update todo_item set checked = true where list_id = $1 and id = $2 and checked = false")
-        .await?;
+// Apply a partial update to an item with a single UPDATE that only touches the supplied fields.
+// Checking an item also checks its descendants and schedules the next occurrence of a recurring
+// item, in the same transaction; unchecking it takes that occurrence back.
+pub async fn update_item(
+    client: &mut Client,
+    list_id: i32,
+    item_id: i32,
+    changes: &UpdateTodoItem,
+    if_match: &IfMatch,
+) -> Result<TodoItem, AppError> {
+    let mut assignments: Vec<String> = Vec::new();
+    let mut params: Vec<&(dyn ToSql + Sync)> = vec![&list_id, &item_id];
 
-    let result = client
-        .execute(&statement, &[&list_id, &item_id])
-        .await?;
+    match &changes.title {
+        Some(Some(title)) => {
+            params.push(title);
+            assignments.push(format!("title = ${}", params.len()));
+        }
+        Some(None) => return Err(null_field("title")),
+        None => (),
+    }
+
+    match &changes.checked {
+        Some(Some(checked)) => {
+            params.push(checked);
+            assignments.push(format!("checked = ${}", params.len()));
+            assignments.push(format!(
+                "completed_at = case when ${} then coalesce(previous.completed_at, now()) end",
+                params.len()
+            ));
+        }
+        Some(None) => return Err(null_field("checked")),
+        None => (),
+    }
 
-    match result {
-        ref updated if *updated == 1 => Ok(true),
-        _ => Ok(false),
+    match &changes.priority {
+        Some(Some(priority)) => {
+            params.push(priority);
+            assignments.push(format!("priority = ${}", params.len()));
+        }
+        Some(None) => return Err(null_field("priority")),
+        None => (),
     }
+
+    // `due_at` is nullable, so an explicit `null` clears it.
+    if let Some(due_at) = &changes.due_at {
+        params.push(due_at);
+        assignments.push(format!("due_at = ${}", params.len()));
+    }
+
+    // So is `recurrence`, which makes the item a one-off again. Checking the item hands its rule
+    // over to the next occurrence instead, and unchecking it takes the rule back, as in
+    // `set_checked`.
+    let checking = changes.checked == Some(Some(true));
+    let unchecking = changes.checked == Some(Some(false));
+
+    if checking {
+        assignments.push("recurrence = null".to_string());
+    } else if let (false, Some(recurrence)) = (unchecking, &changes.recurrence) {
+        params.push(recurrence);
+        assignments.push(format!("recurrence = ${}", params.len()));
+    }
+
+    // Nothing to change: behave like a plain fetch so the response shape stays the same.
+    if assignments.is_empty() {
+        let item = get_item(client, list_id, item_id).await?;
+        if_match.check(item.version)?;
+        return Ok(item);
+    }
+
+    assignments.push("updated_at = now()".to_string());
+
+    let transaction = client.transaction().await?;
+
+    lock_list(&transaction, list_id).await?;
+    if_match.check(lock_item(&transaction, list_id, item_id).await?)?;
+
+    let reclaimed;
+    if unchecking {
+        reclaimed = reclaim_rule(&transaction, list_id, item_id, Some(false)).await?;
+        if let Some(recurrence) = reclaimed.as_ref().or(changes.recurrence.as_ref()) {
+            params.push(recurrence);
+            assignments.push(format!("recurrence = ${}", params.len()));
+        }
+    }
+
+    // Joining the row to itself exposes its values from before the update.
+    let statement = transaction
+        .prepare(&format!(
+            "update todo_item set {} from todo_item previous \
+             where previous.id = todo_item.id \
+             and todo_item.list_id = $1 and todo_item.id = $2 and todo_item.deleted_at is null \
+             returning {}, previous.recurrence as previous_recurrence",
+            assignments.join(", "),
+            ITEM_COLUMNS
+        ))
+        .await?;
+
+    let (item, previous_recurrence) = transaction
+        .query_opt(&statement, &params)
+        .await?
+        .map(|row| {
+            let recurrence: Option<Recurrence> = row.get("previous_recurrence");
+            (TodoItem::from_row_ref(&row).unwrap(), recurrence)
+        })
+        .ok_or_else(|| item_not_found(list_id, item_id))?;
+
+    // A `null` rule sent along with the check (as in snapshots of check revisions, see
+    // `revert_item`) doesn't stop the hand-over; only a new rule replaces the previous one.
+    if checking {
+        let recurrence = changes.recurrence.flatten().or(previous_recurrence);
+        complete_item(&transaction, &item, recurrence).await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(item)
+}
+
+// Check every unchecked descendant of an item, handing the rules of recurring ones over to their
+// next occurrences as `set_checked` does; unchecking a parent leaves its children alone.
+async fn check_descendants(transaction: &Transaction<'_>, item_id: i32) -> Result<(), AppError> {
+    let statement = transaction
+        .prepare(&format!(
+            "with recursive descendant as ( \
+                 select id from todo_item where parent_id = $1 and deleted_at is null \
+                 union all \
+                 select todo_item.id from todo_item join descendant on todo_item.parent_id = descendant.id \
+                 where todo_item.deleted_at is null \
+             ) \
+             update todo_item set checked = true, completed_at = now(), recurrence = null, \
+                 updated_at = now() \
+             from todo_item previous \
+             where previous.id = todo_item.id \
+             and todo_item.id in (select id from descendant) and not todo_item.checked \
+             returning {}, previous.recurrence as previous_recurrence",
+            ITEM_COLUMNS
+        ))
+        .await?;
+
+    for row in transaction.query(&statement, &[&item_id]).await? {
+        if let Some(recurrence) = row.get::<_, Option<Recurrence>>("previous_recurrence") {
+            schedule_next(
+                transaction,
+                &TodoItem::from_row_ref(&row).unwrap(),
+                recurrence,
+            )
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+// Not-found domain errors shared by list and item queries.
+fn list_not_found(list_id: i32) -> AppError {
+    AppError {
+        error_type: NotFoundError,
+        cause: None,
+        message: Some(format!("Todo list {} not found.", list_id)),
+    }
+}
+
+fn item_not_found(list_id: i32, item_id: i32) -> AppError {
+    AppError {
+        error_type: NotFoundError,
+        cause: None,
+        message: Some(format!(
+            "Todo item {} from list {} not found.",
+            item_id, list_id
+        )),
+    }
+}
+
+// Validation error for a non-nullable field explicitly sent as `null`.
+fn null_field(field: &str) -> AppError {
+    AppError {
+        error_type: BadRequestError,
+        cause: None,
+        message: Some(format!("Field '{}' cannot be null.", field)),
+    }
+}
+
+// Move a specific item and its sub-items to the trash, scoped by its list, or return a not-found
+// domain error. Positions of the remaining items are renumbered so they stay gapless.
+pub async fn delete_item(
+    client: &mut Client,
+    list_id: i32,
+    item_id: i32,
+    if_match: &IfMatch,
+) -> Result<(), AppError> {
+    let transaction = client.transaction().await?;
+
+    lock_list(&transaction, list_id).await?;
+    if_match.check(lock_item(&transaction, list_id, item_id).await?)?;
+    remove_item(&transaction, list_id, item_id).await?;
+    renumber_positions(&transaction, list_id).await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+// Move an item and its sub-items to the trash without renumbering positions. They share one
+// `deleted_at` so they are restored together, and give up their positions for `-id`.
+async fn remove_item(
+    transaction: &Transaction<'_>,
+    list_id: i32,
+    item_id: i32,
+) -> Result<(), AppError> {
+    let statement = transaction
+        .prepare(
+            "with recursive subtree as ( \
+                 select id from todo_item where list_id = $1 and id = $2 and deleted_at is null \
+                 union all \
+                 select todo_item.id from todo_item join subtree on todo_item.parent_id = subtree.id \
+                 where todo_item.deleted_at is null \
+             ) \
+             update todo_item set deleted_at = now(), position = -id \
+             where id in (select id from subtree)",
+        )
+        .await?;
+
+    match transaction
+        .execute(&statement, &[&list_id, &item_id])
+        .await?
+    {
+        0 => Err(item_not_found(list_id, item_id)),
+        _ => Ok(()),
+    }
+}
+
+// Set an item's checked state, or flip it when `checked` is `None`; returns the resulting item.
+// `completed_at` keeps the original completion time if the item was already checked, and checking
+// an item also checks its descendants.
+pub async fn check_todo(
+    client: &mut Client,
+    list_id: i32,
+    item_id: i32,
+    checked: Option<bool>,
+    if_match: &IfMatch,
+) -> Result<TodoItem, AppError> {
+    let transaction = client.transaction().await?;
+
+    lock_list(&transaction, list_id).await?;
+    if_match.check(lock_item(&transaction, list_id, item_id).await?)?;
+    let item = set_checked(&transaction, list_id, item_id, checked).await?;
+
+    transaction.commit().await?;
+
+    Ok(item)
+}
+
+// Transaction-scoped body of `check_todo`. Checking a recurring item hands its rule over to a
+// new occurrence, and unchecking it takes the rule back (see `reclaim_rule`).
+async fn set_checked(
+    transaction: &Transaction<'_>,
+    list_id: i32,
+    item_id: i32,
+    checked: Option<bool>,
+) -> Result<TodoItem, AppError> {
+    let reclaimed = reclaim_rule(transaction, list_id, item_id, checked).await?;
+
+    // Joining the row to itself exposes its values from before the update.
+    let statement = transaction
+        .prepare(&format!(
+            "update todo_item set checked = coalesce($3, not previous.checked), \
+             completed_at = case when coalesce($3, not previous.checked) \
+                 then coalesce(previous.completed_at, now()) end, \
+             recurrence = case when coalesce($3, not previous.checked) then null \
+                 when $4 then $5 else previous.recurrence end, \
+             updated_at = now() \
+             from todo_item previous \
+             where previous.id = todo_item.id \
+             and todo_item.list_id = $1 and todo_item.id = $2 and todo_item.deleted_at is null \
+             returning {}, previous.recurrence as previous_recurrence",
+            ITEM_COLUMNS
+        ))
+        .await?;
+
+    let (item, recurrence) = transaction
+        .query_opt(
+            &statement,
+            &[
+                &list_id,
+                &item_id,
+                &checked,
+                &reclaimed.is_some(),
+                &reclaimed.flatten(),
+            ],
+        )
+        .await?
+        .map(|row| {
+            let recurrence: Option<Recurrence> = row.get("previous_recurrence");
+            (TodoItem::from_row_ref(&row).unwrap(), recurrence)
+        })
+        .ok_or_else(|| item_not_found(list_id, item_id))?;
+
+    if item.checked {
+        complete_item(transaction, &item, recurrence).await?;
+    }
+
+    Ok(item)
+}
+
+// Follow-up to checking an item: check its descendants and, if it had a recurrence rule, create
+// its next occurrence.
+async fn complete_item(
+    transaction: &Transaction<'_>,
+    item: &TodoItem,
+    recurrence: Option<Recurrence>,
+) -> Result<(), AppError> {
+    check_descendants(transaction, item.id).await?;
+
+    if let Some(recurrence) = recurrence {
+        schedule_next(transaction, item, recurrence).await?;
+    }
+
+    Ok(())
+}
+
+// Reverse of `schedule_next`, for an item that ends up unchecked once `checked` is applied (`None`
+// toggles). Its live next occurrence is moved to the trash and its rule returned, to be put back on
+// the item, but only while that occurrence is untouched: unchecked, never updated, still in the
+// item's (locked) list, with the same tags and no sub-items. Otherwise the chore lives on there,
+// so the item gets no rule back (`Some(None)`). `None` means the item has no live next occurrence.
+async fn reclaim_rule(
+    transaction: &Transaction<'_>,
+    list_id: i32,
+    item_id: i32,
+    checked: Option<bool>,
+) -> Result<Option<Option<Recurrence>>, AppError> {
+    let statement = transaction
+        .prepare(
+            "select id, recurrence, not checked and version = 1 and list_id = $1 \
+                 and not exists (select 1 from todo_item child where child.parent_id = successor.id) \
+                 and not exists ( \
+                     (select tag_id from todo_item_tag where item_id = successor.id \
+                      except select tag_id from todo_item_tag where item_id = $2) \
+                     union all \
+                     (select tag_id from todo_item_tag where item_id = $2 \
+                      except select tag_id from todo_item_tag where item_id = successor.id) \
+                 ) as untouched \
+             from todo_item successor \
+             where previous_occurrence_id = $2 and deleted_at is null \
+             and exists (select 1 from todo_item where list_id = $1 and id = $2 \
+                         and deleted_at is null and not coalesce($3, not checked)) \
+             order by id desc limit 1 for update",
+        )
+        .await?;
+
+    let successor = match transaction
+        .query_opt(&statement, &[&list_id, &item_id, &checked])
+        .await?
+    {
+        Some(successor) => successor,
+        None => return Ok(None),
+    };
+
+    if !successor.get::<_, bool>("untouched") {
+        return Ok(Some(None));
+    }
+
+    remove_item(transaction, list_id, successor.get("id")).await?;
+    renumber_positions(transaction, list_id).await?;
+
+    Ok(Some(successor.get("recurrence")))
+}
+
+// Create the next, unchecked occurrence of a recurring item at the end of its list, with the same
+// title, parent, priority and tags, linked back to the item through `previous_occurrence_id`.
+async fn schedule_next(
+    transaction: &Transaction<'_>,
+    item: &TodoItem,
+    recurrence: Recurrence,
+) -> Result<(), AppError> {
+    let due_at = recurrence.next_due(item.due_at, Utc::now());
+
+    let statement = transaction
+        .prepare(
+            "with occurrence as ( \
+                 insert into todo_item (list_id, parent_id, title, due_at, priority, recurrence, \
+                     position, previous_occurrence_id) \
+                 select list_id, parent_id, title, $2, priority, $3, \
+                     (select coalesce(max(position), 0) + 1 from todo_item \
+                      where list_id = previous.list_id and deleted_at is null), id \
+                 from todo_item previous where id = $1 \
+                 returning id \
+             ) \
+             insert into todo_item_tag (item_id, tag_id) \
+             select occurrence.id, tag_id from occurrence, todo_item_tag where item_id = $1",
+        )
+        .await?;
+
+    transaction
+        .execute(&statement, &[&item.id, &due_at, &recurrence])
+        .await?;
+
+    Ok(())
+}
+
+// Change an item's title and return the updated item.
+async fn retitle_item(
+    transaction: &Transaction<'_>,
+    list_id: i32,
+    item_id: i32,
+    title: &str,
+) -> Result<TodoItem, AppError> {
+    let statement = transaction
+        .prepare(&format!(
+            "update todo_item set title = $3, updated_at = now() \
+             where list_id = $1 and id = $2 and deleted_at is null returning {}",
+            ITEM_COLUMNS
+        ))
+        .await?;
+
+    transaction
+        .query_opt(&statement, &[&list_id, &item_id, &title])
+        .await?
+        .map(|row| TodoItem::from_row_ref(&row).unwrap())
+        .ok_or_else(|| item_not_found(list_id, item_id))
+}
+
+// Apply a batch of item operations in one transaction. Either every operation succeeds and the
+// per-operation results are returned, or nothing is changed and the first failure is reported.
+// Operations with a `version` fail with 412 if their item has changed since.
+pub async fn bulk_items(
+    client: &mut Client,
+    list_id: i32,
+    operations: &[BulkOperation],
+) -> Result<Vec<BulkOperationResult>, AppError> {
+    let transaction = client.transaction().await?;
+
+    lock_list(&transaction, list_id).await?;
+
+    let mut results = Vec::with_capacity(operations.len());
+
+    for (index, operation) in operations.iter().enumerate() {
+        let item_id = operation.item_id();
+        let outcome = apply_operation(&transaction, list_id, operation).await;
+
+        let item = outcome.map_err(|err| AppError {
+            message: Some(format!(
+                "Operation {} ({} item {}) failed: {}",
+                index,
+                operation.name(),
+                item_id,
+                err.message()
+            )),
+            ..err
+        })?;
+
+        results.push(BulkOperationResult {
+            op: operation.name().to_string(),
+            item_id,
+            item,
+        });
+    }
+
+    renumber_positions(&transaction, list_id).await?;
+
+    transaction.commit().await?;
+
+    Ok(results)
+}
+
+// One operation of `bulk_items`, after checking the item's version if the operation has one.
+async fn apply_operation(
+    transaction: &Transaction<'_>,
+    list_id: i32,
+    operation: &BulkOperation,
+) -> Result<Option<TodoItem>, AppError> {
+    let item_id = operation.item_id();
+
+    if let Some(version) = operation.version() {
+        IfMatch::from_version(Some(version))
+            .check(lock_item(transaction, list_id, item_id).await?)?;
+    }
+
+    match operation {
+        BulkOperation::Check { .. } => set_checked(transaction, list_id, item_id, Some(true))
+            .await
+            .map(Some),
+        BulkOperation::Uncheck { .. } => set_checked(transaction, list_id, item_id, Some(false))
+            .await
+            .map(Some),
+        BulkOperation::Retitle { title, .. } => retitle_item(transaction, list_id, item_id, title)
+            .await
+            .map(Some),
+        BulkOperation::Delete { .. } => remove_item(transaction, list_id, item_id)
+            .await
+            .map(|_| None),
+    }
+}
+
+// List the trash: deleted lists, and deleted items whose list and parent are still live (the
+// others come back with their list or parent).
+pub async fn get_trash(client: &Client) -> Result<Trash, AppError> {
+    let lists = client
+        .prepare("select * from todo_list where deleted_at is not null order by deleted_at desc, id desc")
+        .await?;
+    let items = client
+        .prepare(&format!(
+            "select {} from todo_item where deleted_at is not null \
+             and exists (select 1 from todo_list \
+                 where todo_list.id = todo_item.list_id and todo_list.deleted_at is null) \
+             and not exists (select 1 from todo_item parent \
+                 where parent.id = todo_item.parent_id and parent.deleted_at is not null) \
+             order by deleted_at desc, id desc",
+            ITEM_COLUMNS
+        ))
+        .await?;
+
+    let lists = client
+        .query(&lists, &[])
+        .await?
+        .iter()
+        .map(|row| TodoList::from_row_ref(row).unwrap())
+        .collect::<Vec<TodoList>>();
+    let items = client
+        .query(&items, &[])
+        .await?
+        .iter()
+        .map(|row| TodoItem::from_row_ref(row).unwrap())
+        .collect::<Vec<TodoItem>>();
+
+    Ok(Trash { lists, items })
+}
+
+// Take a list out of the trash together with the items that were deleted with it.
+pub async fn restore_todo(client: &mut Client, list_id: i32) -> Result<TodoList, AppError> {
+    let transaction = client.transaction().await?;
+
+    let restore_items = transaction
+        .prepare(
+            "update todo_item set deleted_at = null where list_id = $1 \
+             and deleted_at = (select deleted_at from todo_list where id = $1)",
+        )
+        .await?;
+    let restore_list = transaction
+        .prepare(
+            "update todo_list set deleted_at = null where id = $1 and deleted_at is not null \
+             returning *",
+        )
+        .await?;
+
+    transaction.execute(&restore_items, &[&list_id]).await?;
+    let list = transaction
+        .query_opt(&restore_list, &[&list_id])
+        .await?
+        .map(|row| TodoList::from_row_ref(&row).unwrap())
+        .ok_or_else(|| not_in_trash(format!("Todo list {}", list_id)))?;
+
+    transaction.commit().await?;
+
+    Ok(list)
+}
+
+// Take an item out of the trash together with the sub-items that were deleted with it; they are
+// appended to the end of the list. Its list and parent item have to be restored first.
+pub async fn restore_item(client: &mut Client, item_id: i32) -> Result<TodoItem, AppError> {
+    let statement = client
+        .prepare("select list_id from todo_item where id = $1 and deleted_at is not null")
+        .await?;
+
+    let list_id: i32 = client
+        .query_opt(&statement, &[&item_id])
+        .await?
+        .map(|row| row.get(0))
+        .ok_or_else(|| not_in_trash(format!("Todo item {}", item_id)))?;
+
+    let transaction = client.transaction().await?;
+
+    let statement = transaction
+        .prepare("select deleted_at is not null from todo_list where id = $1 for update")
+        .await?;
+
+    if transaction.query_one(&statement, &[&list_id]).await?.get(0) {
+        return Err(restore_first(format!("list {}", list_id)));
+    }
+    lock_list(&transaction, list_id).await?;
+
+    // Re-read under the list lock: the item may have been restored or moved in the meantime.
+    let statement = transaction
+        .prepare(
+            "select parent.id from todo_item \
+             left join todo_item parent on parent.id = todo_item.parent_id \
+                 and parent.deleted_at is not null \
+             where todo_item.list_id = $1 and todo_item.id = $2 \
+             and todo_item.deleted_at is not null",
+        )
+        .await?;
+
+    let deleted_parent: Option<i32> = transaction
+        .query_opt(&statement, &[&list_id, &item_id])
+        .await?
+        .map(|row| row.get(0))
+        .ok_or_else(|| not_in_trash(format!("Todo item {}", item_id)))?;
+
+    if let Some(parent_id) = deleted_parent {
+        return Err(restore_first(format!("parent item {}", parent_id)));
+    }
+
+    let statement = transaction
+        .prepare(
+            "with recursive subtree as ( \
+                 select id, deleted_at from todo_item where id = $2 \
+                 union all \
+                 select todo_item.id, todo_item.deleted_at \
+                 from todo_item join subtree on todo_item.parent_id = subtree.id \
+                 where todo_item.deleted_at = subtree.deleted_at \
+             ) \
+             update todo_item set deleted_at = null, position = restored.position \
+             from (select id, (row_number() over (order by id) + \
+                       (select coalesce(max(position), 0) from todo_item \
+                        where list_id = $1 and deleted_at is null))::int as position \
+                   from subtree) restored \
+             where todo_item.id = restored.id",
+        )
+        .await?;
+
+    transaction
+        .execute(&statement, &[&list_id, &item_id])
+        .await?;
+
+    let statement = transaction
+        .prepare(&format!(
+            "select {} from todo_item where id = $1",
+            ITEM_COLUMNS
+        ))
+        .await?;
+
+    let item = transaction
+        .query_one(&statement, &[&item_id])
+        .await
+        .map(|row| TodoItem::from_row_ref(&row).unwrap())?;
+
+    transaction.commit().await?;
+
+    Ok(item)
+}
+
+// Permanently delete lists and items that went to the trash before `deleted_before`; returns the
+// number of rows removed (not counting items removed with their list).
+pub async fn purge_trash(client: &Client, deleted_before: DateTime<Utc>) -> Result<u64, AppError> {
+    let lists = client
+        .prepare("delete from todo_list where deleted_at < $1")
+        .await?;
+    let items = client
+        .prepare("delete from todo_item where deleted_at < $1")
+        .await?;
+
+    let purged = client.execute(&lists, &[&deleted_before]).await?
+        + client.execute(&items, &[&deleted_before]).await?;
+
+    Ok(purged)
+}
+
+fn not_in_trash(what: String) -> AppError {
+    AppError {
+        error_type: NotFoundError,
+        cause: None,
+        message: Some(format!("{} is not in the trash.", what)),
+    }
+}
+
+fn restore_first(what: String) -> AppError {
+    AppError {
+        error_type: BadRequestError,
+        cause: None,
+        message: Some(format!("Restore {} first.", what)),
+    }
+}
+
+// Recorded changes to an item, newest first. Trashed items keep their history.
+pub async fn get_item_history(
+    client: &Client,
+    list_id: i32,
+    item_id: i32,
+) -> Result<Vec<HistoryEntry>, AppError> {
+    let exists = client
+        .prepare("select 1 from todo_item where list_id = $1 and id = $2")
+        .await?;
+
+    if client
+        .query_opt(&exists, &[&list_id, &item_id])
+        .await?
+        .is_none()
+    {
+        return Err(item_not_found(list_id, item_id));
+    }
+
+    let statement = client
+        .prepare("select * from history where item_id = $1 order by version desc")
+        .await?;
+
+    let history = client
+        .query(&statement, &[&item_id])
+        .await?
+        .iter()
+        .map(|row| HistoryEntry::from_row_ref(row).unwrap())
+        .collect::<Vec<HistoryEntry>>();
+
+    Ok(history)
+}
+
+// Put an item's title, checked state, due date, priority and recurrence back to what they were at
+// `version`. This is an ordinary update, so it is recorded in the history as well; position,
+// parent and tags are left alone.
+pub async fn revert_item(
+    client: &mut Client,
+    list_id: i32,
+    item_id: i32,
+    version: i32,
+    if_match: &IfMatch,
+) -> Result<TodoItem, AppError> {
+    let statement = client
+        .prepare("select new_values from history where item_id = $1 and version = $2")
+        .await?;
+
+    let values: serde_json::Value = client
+        .query_opt(&statement, &[&item_id, &version])
+        .await?
+        .map(|row| row.get(0))
+        .ok_or_else(|| revision_not_found(item_id, version))?;
+
+    // The snapshot has the same field names as an update payload; other columns are ignored.
+    let changes: UpdateTodoItem = serde_json::from_value(values).map_err(|err| AppError {
+        error_type: DbError,
+        cause: Some(err.to_string()),
+        message: None,
+    })?;
+
+    update_item(client, list_id, item_id, &changes, if_match).await
+}
+
+fn revision_not_found(item_id: i32, version: i32) -> AppError {
+    AppError {
+        error_type: NotFoundError,
+        cause: None,
+        message: Some(format!(
+            "Version {} of todo item {} not found in its history.",
+            version, item_id
+        )),
+    }
+}
+
+// Columns selected for every `Template`: the row itself plus its items as a `template_item[]`.
+const TEMPLATE_COLUMNS: &str = "template.*, array(\
+     select template_item from template_item \
+     where template_item.template_id = template.id order by position) as items";
+
+// Save a list's items (not the trashed ones) as a new template. Titles, priorities, recurrence
+// rules and nesting are kept; checked state, due dates and tags are not.
+pub async fn save_template(
+    client: &mut Client,
+    list_id: i32,
+    name: Option<&str>,
+) -> Result<Template, AppError> {
+    let transaction = client.transaction().await?;
+
+    let statement = transaction
+        .prepare(
+            "insert into template (name) \
+             select coalesce($2, title) from todo_list where id = $1 and deleted_at is null \
+             returning id",
+        )
+        .await?;
+
+    let template_id: i32 = transaction
+        .query_opt(&statement, &[&list_id, &name])
+        .await?
+        .map(|row| row.get(0))
+        .ok_or_else(|| list_not_found(list_id))?;
+
+    let statement = transaction
+        .prepare(
+            "insert into template_item \
+                 (template_id, position, parent_position, title, priority, recurrence) \
+             select $1, todo_item.position, parent.position, todo_item.title, todo_item.priority, \
+                 todo_item.recurrence \
+             from todo_item left join todo_item parent on parent.id = todo_item.parent_id \
+             where todo_item.list_id = $2 and todo_item.deleted_at is null",
+        )
+        .await?;
+
+    transaction
+        .execute(&statement, &[&template_id, &list_id])
+        .await?;
+
+    transaction.commit().await?;
+
+    get_template(client, template_id).await
+}
+
+// Fetch all templates by name.
+pub async fn get_templates(client: &Client) -> Result<Vec<Template>, AppError> {
+    let statement = client
+        .prepare(&format!(
+            "select {} from template order by name, id",
+            TEMPLATE_COLUMNS
+        ))
+        .await?;
+
+    let templates = client
+        .query(&statement, &[])
+        .await?
+        .iter()
+        .map(|row| Template::from_row_ref(row).unwrap())
+        .collect::<Vec<Template>>();
+
+    Ok(templates)
+}
+
+// Fetch a single template by id or return a not-found domain error.
+pub async fn get_template(client: &Client, template_id: i32) -> Result<Template, AppError> {
+    let statement = client
+        .prepare(&format!(
+            "select {} from template where id = $1",
+            TEMPLATE_COLUMNS
+        ))
+        .await?;
+
+    client
+        .query_opt(&statement, &[&template_id])
+        .await?
+        .map(|row| Template::from_row_ref(&row).unwrap())
+        .ok_or_else(|| template_not_found(template_id))
+}
+
+// Create a new list from a template, with its placeholders filled in, in one transaction.
+pub async fn instantiate_template(
+    client: &mut Client,
+    template_id: i32,
+    instantiate: &InstantiateTemplate,
+) -> Result<TodoList, AppError> {
+    let template = get_template(client, template_id).await?;
+
+    let title = instantiate.fill(instantiate.title.as_deref().unwrap_or(&template.name));
+    let (positions, titles): (Vec<i32>, Vec<String>) = template
+        .items
+        .iter()
+        .map(|item| (item.position, instantiate.fill(&item.title)))
+        .unzip();
+
+    let transaction = client.transaction().await?;
+
+    let statement = transaction
+        .prepare("insert into todo_list (title) values ($1) returning *")
+        .await?;
+
+    let list = transaction
+        .query_one(&statement, &[&title])
+        .await
+        .map(|row| TodoList::from_row_ref(&row).unwrap())?;
+
+    // Ids are drawn up front so sub-items can point at their parents in the same statement.
+    let statement = transaction
+        .prepare(
+            "with new_id as ( \
+                 select position, nextval(pg_get_serial_sequence('todo_item', 'id'))::int as id \
+                 from template_item where template_id = $2 \
+             ) \
+             insert into todo_item \
+                 (id, list_id, parent_id, title, priority, recurrence, position) \
+             select new_id.id, $1, parent.id, filled.title, template_item.priority, \
+                 template_item.recurrence, template_item.position \
+             from template_item \
+             join new_id on new_id.position = template_item.position \
+             join unnest($3::int[], $4::varchar[]) as filled (position, title) \
+                 on filled.position = template_item.position \
+             left join new_id parent on parent.position = template_item.parent_position \
+             where template_item.template_id = $2",
+        )
+        .await?;
+
+    transaction
+        .execute(&statement, &[&list.id, &template_id, &positions, &titles])
+        .await?;
+
+    transaction.commit().await?;
+
+    Ok(list)
+}
+
+fn template_not_found(template_id: i32) -> AppError {
+    AppError {
+        error_type: NotFoundError,
+        cause: None,
+        message: Some(format!("Template {} not found.", template_id)),
+    }
+}
+
+// Turn a unique violation on `tag.name` into a client error instead of a 500.
+fn tag_name_taken(name: &str) -> impl Fn(tokio_postgres::Error) -> AppError + '_ {
+    move |err| match err.code() {
+        Some(code) if *code == SqlState::UNIQUE_VIOLATION => AppError {
+            error_type: BadRequestError,
+            cause: Some(err.to_string()),
+            message: Some(format!("Tag '{}' already exists.", name)),
+        },
+        _ => AppError::from(err),
+    }
+}
+
+fn tag_not_found(tag_id: i32) -> AppError {
+    AppError {
+        error_type: NotFoundError,
+        cause: None,
+        message: Some(format!("Tag {} not found.", tag_id)),
+    }
+}
+
+// Insert a new tag; names are unique.
+pub async fn create_tag(client: &Client, name: &str) -> Result<Tag, AppError> {
+    let statement = client
+        .prepare("insert into tag (name) values ($1) returning *")
+        .await?;
+
+    let row = client
+        .query_one(&statement, &[&name])
+        .await
+        .map_err(tag_name_taken(name))?;
+
+    Ok(Tag::from_row_ref(&row).unwrap())
+}
+
+// Fetch all tags alphabetically.
+pub async fn get_tags(client: &Client) -> Result<Vec<Tag>, AppError> {
+    let statement = client.prepare("select * from tag order by name").await?;
+
+    let tags = client
+        .query(&statement, &[])
+        .await?
+        .iter()
+        .map(|row| Tag::from_row_ref(row).unwrap())
+        .collect::<Vec<Tag>>();
+
+    Ok(tags)
+}
+
+// Fetch a single tag by id or return a not-found domain error.
+pub async fn get_tag(client: &Client, tag_id: i32) -> Result<Tag, AppError> {
+    let statement = client.prepare("select * from tag where id = $1").await?;
+
+    let maybe_tag = client
+        .query_opt(&statement, &[&tag_id])
+        .await?
+        .map(|row| Tag::from_row_ref(&row).unwrap());
+
+    maybe_tag.ok_or_else(|| tag_not_found(tag_id))
+}
+
+// CTE bumping every item tagged with tag `$1`, since their embedded tags change with the tag.
+const TOUCH_TAGGED_ITEMS: &str = "touched as (update todo_item set updated_at = now() \
+     where id in (select item_id from todo_item_tag where tag_id = $1))";
+
+// Rename a tag; every item carrying it picks up the new name (and a new version).
+pub async fn rename_tag(client: &Client, tag_id: i32, name: &str) -> Result<Tag, AppError> {
+    let statement = client
+        .prepare(&format!(
+            "with {} update tag set name = $2 where id = $1 returning *",
+            TOUCH_TAGGED_ITEMS
+        ))
+        .await?;
+
+    let maybe_tag = client
+        .query_opt(&statement, &[&tag_id, &name])
+        .await
+        .map_err(tag_name_taken(name))?
+        .map(|row| Tag::from_row_ref(&row).unwrap());
+
+    maybe_tag.ok_or_else(|| tag_not_found(tag_id))
+}
+
+// Delete a tag; it is detached from all items by the foreign key cascade.
+pub async fn delete_tag(client: &Client, tag_id: i32) -> Result<(), AppError> {
+    let statement = client
+        .prepare(&format!(
+            "with {} delete from tag where id = $1",
+            TOUCH_TAGGED_ITEMS
+        ))
+        .await?;
+
+    match client.execute(&statement, &[&tag_id]).await? {
+        0 => Err(tag_not_found(tag_id)),
+        _ => Ok(()),
+    }
+}
+
+// Attach a tag to an item (a no-op if already attached) and return the updated item.
+pub async fn attach_tag(
+    client: &mut Client,
+    list_id: i32,
+    item_id: i32,
+    tag_id: i32,
+    if_match: &IfMatch,
+) -> Result<TodoItem, AppError> {
+    get_tag(client, tag_id).await?;
+
+    let transaction = client.transaction().await?;
+
+    lock_list(&transaction, list_id).await?;
+    if_match.check(lock_item(&transaction, list_id, item_id).await?)?;
+
+    let statement = transaction
+        .prepare(
+            "insert into todo_item_tag (item_id, tag_id) values ($1, $2) on conflict do nothing",
+        )
+        .await?;
+
+    transaction
+        .execute(&statement, &[&item_id, &tag_id])
+        .await?;
+
+    let item = touch_item(&transaction, list_id, item_id).await?;
+
+    transaction.commit().await?;
+
+    Ok(item)
+}
+
+// Detach a tag from an item (a no-op if not attached) and return the updated item.
+pub async fn detach_tag(
+    client: &mut Client,
+    list_id: i32,
+    item_id: i32,
+    tag_id: i32,
+    if_match: &IfMatch,
+) -> Result<TodoItem, AppError> {
+    let transaction = client.transaction().await?;
+
+    lock_list(&transaction, list_id).await?;
+    if_match.check(lock_item(&transaction, list_id, item_id).await?)?;
+
+    let statement = transaction
+        .prepare("delete from todo_item_tag where item_id = $1 and tag_id = $2")
+        .await?;
+
+    transaction
+        .execute(&statement, &[&item_id, &tag_id])
+        .await?;
+
+    let item = touch_item(&transaction, list_id, item_id).await?;
+
+    transaction.commit().await?;
+
+    Ok(item)
+}
+
+// Bump an item's `updated_at` after a change to its related rows and return it.
+async fn touch_item(
+    transaction: &Transaction<'_>,
+    list_id: i32,
+    item_id: i32,
+) -> Result<TodoItem, AppError> {
+    let statement = transaction
+        .prepare(&format!(
+            "update todo_item set updated_at = now() \
+             where list_id = $1 and id = $2 and deleted_at is null returning {}",
+            ITEM_COLUMNS
+        ))
+        .await?;
+
+    let maybe_item = transaction
+        .query_opt(&statement, &[&list_id, &item_id])
+        .await?
+        .map(|row| TodoItem::from_row_ref(&row).unwrap());
+
+    maybe_item.ok_or_else(|| item_not_found(list_id, item_id))
 }
@@ -8,9 +8,13 @@ use tokio_postgres::error::Error;
 
 // Coarse-grained error categories to decouple DB/internal errors from HTTP mapping.
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum AppErrorType {
     DbError,
     NotFoundError,
+    BadRequestError,
+    // An `If-Match` precondition did not hold: the resource changed since the client read it.
+    PreconditionFailedError,
 }
 
 // Carries optional user-facing message and internal cause for logging.
@@ -24,7 +28,7 @@ pub struct AppError {
 impl AppError {
     // Prefer a user-provided message; otherwise return a safe default per error type.
     pub fn message(&self) -> String {
-        match &*self {
+        match self {
             AppError {
                 message: Some(message),
                 ..
@@ -34,6 +38,16 @@ impl AppError {
                 error_type: AppErrorType::NotFoundError,
                 ..
             } => "The requested item was not found".to_string(),
+            AppError {
+                message: None,
+                error_type: AppErrorType::BadRequestError,
+                ..
+            } => "The request is invalid".to_string(),
+            AppError {
+                message: None,
+                error_type: AppErrorType::PreconditionFailedError,
+                ..
+            } => "The resource has been modified since it was last fetched".to_string(),
             _ => "An unexpected error has occurred".to_string(),
         }
     }
@@ -77,6 +91,8 @@ impl ResponseError for AppError {
         match self.error_type {
             AppErrorType::DbError => StatusCode::INTERNAL_SERVER_ERROR,
             AppErrorType::NotFoundError => StatusCode::NOT_FOUND,
+            AppErrorType::BadRequestError => StatusCode::BAD_REQUEST,
+            AppErrorType::PreconditionFailedError => StatusCode::PRECONDITION_FAILED,
         }
     }
     fn error_response(&self) -> HttpResponse {
@@ -156,4 +172,40 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn test_bad_request_error_status_code() {
+        let expected = 400;
+
+        let bad_request = AppError {
+            message: None,
+            cause: None,
+            error_type: AppErrorType::BadRequestError,
+        };
+
+        assert_eq!(
+            bad_request.status_code(),
+            expected,
+            "Status code for BadRequestError should be {}",
+            expected
+        );
+    }
+
+    #[test]
+    fn test_precondition_failed_error_status_code() {
+        let expected = 412;
+
+        let precondition_failed = AppError {
+            message: None,
+            cause: None,
+            error_type: AppErrorType::PreconditionFailedError,
+        };
+
+        assert_eq!(
+            precondition_failed.status_code(),
+            expected,
+            "Status code for PreconditionFailedError should be {}",
+            expected
+        );
+    }
 }
@@ -0,0 +1,205 @@
+// File: src/etag.rs
+// High-level: `ETag`s for lists, items and collections, with the `If-Match` precondition checked
+// before changing them and the `If-None-Match` one that turns unchanged reads into 304s.
+use crate::errors::{AppError, AppErrorType};
+use actix_web::http::header::{self, ETag, EntityTag, Header};
+use actix_web::{dev::Payload, FromRequest, HttpRequest, HttpResponse};
+use futures::future::{ready, Ready};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+// Strong `ETag` for a row version, e.g. `"3"`.
+pub fn etag(version: i32) -> ETag {
+    ETag(EntityTag::new_strong(version.to_string()))
+}
+
+// Strong `ETag` for a row version followed by counts taken from other rows, e.g. `"3.12.7"`. The
+// counts let `If-None-Match` notice changes the version doesn't; `If-Match` only reads the version.
+pub fn etag_with_counts(version: i32, counts: &[i64]) -> ETag {
+    let tag = counts.iter().fold(version.to_string(), |tag, count| {
+        format!("{}.{}", tag, count)
+    });
+
+    ETag(EntityTag::new_strong(tag))
+}
+
+// 200 with a single list or item and its `ETag`, or an empty 304 if the client has it.
+pub fn tagged_json<T: Serialize>(req: &HttpRequest, etag: ETag, body: &T) -> HttpResponse {
+    match not_modified(req, &etag) {
+        true => HttpResponse::NotModified().insert_header(etag).finish(),
+        false => HttpResponse::Ok().insert_header(etag).json(body),
+    }
+}
+
+// 200 with a collection tagged with a hash of its JSON, or an empty 304 if the client has it.
+// Collections have no version of their own, and hashing also catches items leaving them.
+pub fn hashed_json<T: Serialize>(req: &HttpRequest, body: &T) -> HttpResponse {
+    let json = serde_json::to_vec(body).unwrap();
+    let etag = ETag(EntityTag::new_strong(format!(
+        "{:x}",
+        Sha256::digest(&json)
+    )));
+
+    match not_modified(req, &etag) {
+        true => HttpResponse::NotModified().insert_header(etag).finish(),
+        false => HttpResponse::Ok()
+            .insert_header(etag)
+            .content_type("application/json")
+            .body(json),
+    }
+}
+
+// Whether `If-None-Match` lists the current tag (or is `*`). A malformed header is ignored.
+fn not_modified(req: &HttpRequest, etag: &ETag) -> bool {
+    if !req.headers().contains_key(header::IF_NONE_MATCH) {
+        return false;
+    }
+
+    match header::IfNoneMatch::parse(req) {
+        Ok(header::IfNoneMatch::Any) => true,
+        Ok(header::IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag.0)),
+        Err(_) => false,
+    }
+}
+
+// Extractor for the `If-Match` header. Without the header, or with `*`, any version matches;
+// otherwise the current version has to be one of the listed (strong) tags.
+#[derive(Debug, Default)]
+pub struct IfMatch {
+    versions: Option<Vec<i32>>,
+}
+
+impl IfMatch {
+    pub fn from_request_headers(req: &HttpRequest) -> Result<IfMatch, AppError> {
+        if !req.headers().contains_key(header::IF_MATCH) {
+            return Ok(IfMatch::default());
+        }
+
+        let versions = match header::IfMatch::parse(req) {
+            Ok(header::IfMatch::Any) => None,
+            // Weak tags never match `If-Match`, and tags we did not issue match no version. Counts
+            // after the version (see `etag_with_counts`) are ignored.
+            Ok(header::IfMatch::Items(tags)) => Some(
+                tags.iter()
+                    .filter(|tag| !tag.weak)
+                    .filter_map(|tag| tag.tag().split('.').next().unwrap().parse().ok())
+                    .collect(),
+            ),
+            Err(_) => {
+                return Err(AppError {
+                    error_type: AppErrorType::BadRequestError,
+                    cause: None,
+                    message: Some("Invalid If-Match header.".to_string()),
+                })
+            }
+        };
+
+        Ok(IfMatch { versions })
+    }
+
+    // Same precondition for a version sent in the request body, e.g. by bulk operations; `None`
+    // matches any version.
+    pub fn from_version(version: Option<i32>) -> IfMatch {
+        IfMatch {
+            versions: version.map(|version| vec![version]),
+        }
+    }
+
+    // Fail with 412 Precondition Failed unless `version` satisfies the precondition.
+    pub fn check(&self, version: i32) -> Result<(), AppError> {
+        match &self.versions {
+            Some(versions) if !versions.contains(&version) => Err(AppError {
+                error_type: AppErrorType::PreconditionFailedError,
+                cause: None,
+                message: Some(format!(
+                    "Version {} does not match the expected version; fetch the latest version and retry.",
+                    version
+                )),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl FromRequest for IfMatch {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(IfMatch::from_request_headers(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{etag, etag_with_counts, hashed_json, tagged_json, IfMatch};
+    use actix_web::test::TestRequest;
+
+    fn if_match(value: &str) -> IfMatch {
+        let req = TestRequest::default()
+            .insert_header(("If-Match", value))
+            .to_http_request();
+
+        IfMatch::from_request_headers(&req).unwrap()
+    }
+
+    #[test]
+    fn test_without_if_match() {
+        let req = TestRequest::default().to_http_request();
+        let if_match = IfMatch::from_request_headers(&req).unwrap();
+
+        assert!(if_match.check(7).is_ok(), "No header should match anything");
+    }
+
+    #[test]
+    fn test_if_match_versions() {
+        assert!(if_match("*").check(7).is_ok(), "* should match anything");
+        assert!(if_match(r#""3", "7""#).check(7).is_ok(), "Listed version");
+        assert!(if_match(r#""3""#).check(7).is_err(), "Stale version");
+        assert!(
+            if_match(r#"W/"7""#).check(7).is_err(),
+            "Weak tags never match"
+        );
+        assert!(
+            if_match(r#""abc""#).check(7).is_err(),
+            "Foreign tags never match"
+        );
+        assert!(
+            if_match(r#""7.12.3""#).check(7).is_ok(),
+            "Counts after the version are ignored"
+        );
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let req = TestRequest::default()
+            .insert_header(("If-None-Match", r#"W/"3""#))
+            .to_http_request();
+
+        assert_eq!(
+            tagged_json(&req, etag(3), &"item").status(),
+            304,
+            "Unchanged"
+        );
+        assert_eq!(tagged_json(&req, etag(4), &"item").status(), 200, "Changed");
+        assert_eq!(
+            tagged_json(&req, etag_with_counts(3, &[2, 1]), &"list").status(),
+            200,
+            "Changed counts"
+        );
+    }
+
+    #[test]
+    fn test_hashed_json() {
+        let fresh = hashed_json(&TestRequest::default().to_http_request(), &[1, 2]);
+        let etag = fresh.headers().get("ETag").unwrap().clone();
+
+        let req = TestRequest::default()
+            .insert_header(("If-None-Match", etag))
+            .to_http_request();
+
+        assert_eq!(hashed_json(&req, &[1, 2]).status(), 304, "Same body");
+        assert_eq!(hashed_json(&req, &[1]).status(), 200, "Different body");
+    }
+}
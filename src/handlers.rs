@@ -2,11 +2,18 @@
 // High-level: HTTP endpoint handlers. Each handler validates inputs, acquires a DB client from the pool,
 // delegates to the data-access layer, and maps results/errors to HTTP responses with structured logging.
 use crate::db;
-use crate::models::{AppState, CreateTodoItem, CreateTodoList, ResultResponse, Status};
+use crate::models::{
+    AppState, BulkItems, CheckTodoItem, CreateTodoItem, CreateTodoList, DuplicateTodoList,
+    InstantiateTemplate, ItemFilter, ListFilter, MoveTodoItem, ReorderItems, SaveTemplate,
+    SearchQuery, Status, TagName, TodoItemNode, TrashKind, UpdateTodoItem, UpdateTodoList,
+};
 
-use crate::errors::AppError;
-use actix_web::{web, HttpResponse, Responder};
+use crate::errors::{AppError, AppErrorType};
+use crate::etag::{etag, etag_with_counts, hashed_json, tagged_json, IfMatch};
+use crate::pagination::{Page, Pagination};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 use deadpool_postgres::{Client, Pool, PoolError};
+use serde::de::DeserializeOwned;
 use slog::{crit, error, o, Logger};
 
 // Acquire a client from the shared pool. We log the pool error details but return a generic HTTP error.
@@ -28,6 +35,22 @@ fn log_error(log: Logger) -> impl Fn(AppError) -> AppError {
         err
     }
 }
+
+// Parse an optional JSON body: `None` when the body is empty, a 400 when it is malformed.
+// `Option<web::Json<T>>` can't be used for this, as it turns malformed bodies into `None` too.
+fn optional_json<T: DeserializeOwned>(body: &web::Bytes) -> Result<Option<T>, AppError> {
+    if body.is_empty() {
+        return Ok(None);
+    }
+
+    serde_json::from_slice(body)
+        .map(Some)
+        .map_err(|err| AppError {
+            error_type: AppErrorType::BadRequestError,
+            cause: None,
+            message: Some(format!("Invalid JSON body: {}", err)),
+        })
+}
 // Simple readiness endpoint so clients (and tests) can verify the service is up.
 pub async fn status() -> Result<impl Responder, AppError> {
     Ok(HttpResponse::Ok().json(Status {
@@ -35,16 +58,21 @@ pub async fn status() -> Result<impl Responder, AppError> {
     }))
 }
 
-// List all todo lists. No input required.
-pub async fn todos(state: web::Data<AppState>) -> Result<impl Responder, AppError> {
+// List todo lists a page at a time with `?limit=&cursor=`; `?archived=true` lists archived ones.
+pub async fn todos(
+    filter: web::Query<ListFilter>,
+    pagination: Pagination,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
     let sublog = state.log.new(o!("handler" => "create_todo"));
 
     let client: Client = get_client(&state.pool, &sublog).await?;
 
-    let result = db::get_todos(&client).await;
+    let result = db::get_todos(&client, &filter, &pagination).await;
 
     result
-        .map(|todos| HttpResponse::Ok().json(todos))
+        .map(|todos| hashed_json(&req, &todos))
         .map_err(log_error(sublog))
 }
 
@@ -64,13 +92,18 @@ pub async fn create_todo(
     let result = db::create_todo(&client, &title).await;
 
     result
-        .map(|todo| HttpResponse::Ok().json(todo))
+        .map(|todo| {
+            HttpResponse::Ok()
+                .insert_header(etag(todo.version))
+                .json(todo)
+        })
         .map_err(log_error(sublog))
 }
 
 // Fetch a specific todo list by id.
 pub async fn get_todo(
     list_id: web::Path<(i32,)>,
+    req: HttpRequest,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, AppError> {
     let sublog = state.log.new(o!(
@@ -83,32 +116,162 @@ pub async fn get_todo(
     let result = db::get_todo(&client, list_id.0).await;
 
     result
-        .map(|todo| HttpResponse::Ok().json(todo))
+        .map(|todo| {
+            let etag = etag_with_counts(todo.list.version, &[todo.item_count, todo.checked_count]);
+            tagged_json(&req, etag, &todo)
+        })
+        .map_err(log_error(sublog))
+}
+
+// Partially update a todo list (e.g. rename it). Only fields present in the payload are changed.
+pub async fn update_todo(
+    list_id: web::Path<(i32,)>,
+    todo_list: web::Json<UpdateTodoList>,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let title = todo_list.into_inner().title;
+    let sublog = state.log.new(o!(
+        "handler" => "update_todo",
+        "list_id" => list_id.0,
+        "todo_list" => title.clone()
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::update_todo(&mut client, list_id.0, title.as_deref(), &if_match).await;
+
+    result
+        .map(|todo| {
+            HttpResponse::Ok()
+                .insert_header(etag(todo.version))
+                .json(todo)
+        })
+        .map_err(log_error(sublog))
+}
+
+// Archive a todo list, making it read-only and hiding it from the default listing.
+pub async fn archive_todo(
+    list_id: web::Path<(i32,)>,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    set_archived(list_id.0, true, if_match, state).await
+}
+
+// Unarchive a todo list.
+pub async fn unarchive_todo(
+    list_id: web::Path<(i32,)>,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    set_archived(list_id.0, false, if_match, state).await
+}
+
+async fn set_archived(
+    list_id: i32,
+    archived: bool,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "set_archived",
+        "list_id" => list_id,
+        "archived" => archived
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::archive_todo(&mut client, list_id, archived, &if_match).await;
+
+    result
+        .map(|todo| {
+            HttpResponse::Ok()
+                .insert_header(etag(todo.version))
+                .json(todo)
+        })
+        .map_err(log_error(sublog))
+}
+
+// Copy a todo list with all of its items; returns the new list and how many items were copied.
+pub async fn duplicate_todo(
+    list_id: web::Path<(i32,)>,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let duplicate: DuplicateTodoList = optional_json(&body)?.unwrap_or_default();
+    let sublog = state.log.new(o!(
+        "handler" => "duplicate_todo",
+        "list_id" => list_id.0,
+        "reset_checked" => duplicate.reset_checked
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::duplicate_todo(&mut client, list_id.0, &duplicate).await;
+
+    result
+        .map(|duplicated| {
+            HttpResponse::Ok()
+                .insert_header(etag(duplicated.list.version))
+                .json(duplicated)
+        })
+        .map_err(log_error(sublog))
+}
+
+// Move a todo list and all of its items to the trash.
+pub async fn delete_todo(
+    list_id: web::Path<(i32,)>,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "delete_todo",
+        "list_id" => list_id.0
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::delete_todo(&mut client, list_id.0, &if_match).await;
+
+    result
+        .map(|_| HttpResponse::NoContent().finish())
         .map_err(log_error(sublog))
 }
+
 // Create a new item in a given list.
 pub async fn create_item(
     list_id: web::Path<(i32,)>,
     todo_item: web::Json<CreateTodoItem>,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, AppError> {
-    let title = todo_item.into_inner().title;
-    let sublog = state
-        .log
-        .new(o!("handler" => "create_item", "list_id" => list_id.0, "todo_item" => title.clone()));
+    let todo_item = todo_item.into_inner();
+    let sublog = state.log.new(o!(
+        "handler" => "create_item",
+        "list_id" => list_id.0,
+        "todo_item" => todo_item.title.clone()
+    ));
 
-    let client: Client = get_client(&state.pool, &sublog).await?;
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
 
-    let result = db::create_item(&client, list_id.0, &title).await;
+    let result = db::create_item(&mut client, list_id.0, None, &todo_item).await;
 
     result
-        .map(|item| HttpResponse::Ok().json(item))
+        .map(|item| {
+            HttpResponse::Ok()
+                .insert_header(etag(item.version))
+                .json(item)
+        })
         .map_err(log_error(sublog))
 }
 
-// List items in a given todo list.
+// List a page of items in a given todo list, optionally filtered, sorted, or nested with
+// `?tree=true` (nesting applies within the page).
 pub async fn items(
     list_id: web::Path<(i32,)>,
+    filter: web::Query<ItemFilter>,
+    pagination: Pagination,
+    req: HttpRequest,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, AppError> {
     let sublog = state.log.new(o!(
@@ -118,16 +281,212 @@ pub async fn items(
 
     let client: Client = get_client(&state.pool, &sublog).await?;
 
-    let result = db::get_items(&client, list_id.0).await;
+    let result = db::get_items(&client, list_id.0, &filter, &pagination).await;
+
+    result
+        .map(|page| match filter.tree {
+            Some(true) => hashed_json(
+                &req,
+                &Page {
+                    items: TodoItemNode::build_tree(page.items),
+                    next_cursor: page.next_cursor,
+                },
+            ),
+            _ => hashed_json(&req, &page),
+        })
+        .map_err(log_error(sublog))
+}
+
+// Rearrange the items of a list; returns them in their new order.
+pub async fn reorder_items(
+    list_id: web::Path<(i32,)>,
+    reorder: web::Json<ReorderItems>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "reorder_items",
+        "list_id" => list_id.0
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::reorder_items(&mut client, list_id.0, &reorder).await;
 
     result
         .map(|items| HttpResponse::Ok().json(items))
         .map_err(log_error(sublog))
 }
 
+// Apply several item operations (check, uncheck, delete, retitle) atomically.
+pub async fn bulk_items(
+    list_id: web::Path<(i32,)>,
+    bulk: web::Json<BulkItems>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "bulk_items",
+        "list_id" => list_id.0,
+        "operations" => bulk.operations.len(),
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::bulk_items(&mut client, list_id.0, &bulk.operations).await;
+
+    result
+        .map(|results| HttpResponse::Ok().json(results))
+        .map_err(log_error(sublog))
+}
+
+// Create a sub-item under an existing item of the same list.
+pub async fn create_child(
+    params: web::Path<(i32, i32)>,
+    todo_item: web::Json<CreateTodoItem>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let todo_item = todo_item.into_inner();
+    let sublog = state.log.new(o!(
+        "handler" => "create_child",
+        "list_id" => params.0,
+        "item_id" => params.1,
+        "todo_item" => todo_item.title.clone()
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::create_item(&mut client, params.0, Some(params.1), &todo_item).await;
+
+    result
+        .map(|item| {
+            HttpResponse::Ok()
+                .insert_header(etag(item.version))
+                .json(item)
+        })
+        .map_err(log_error(sublog))
+}
+
+// List the direct children of an item.
+pub async fn children(
+    params: web::Path<(i32, i32)>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "children",
+        "list_id" => params.0,
+        "item_id" => params.1,
+    ));
+
+    let client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::get_children(&client, params.0, params.1).await;
+
+    result
+        .map(|items| hashed_json(&req, &items))
+        .map_err(log_error(sublog))
+}
+
+// Search items across all lists using the same filters and sorting as `items`.
+pub async fn search_items(
+    filter: web::Query<ItemFilter>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!("handler" => "search_items"));
+
+    let client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::search_items(&client, &filter).await;
+
+    result
+        .map(|items| hashed_json(&req, &items))
+        .map_err(log_error(sublog))
+}
+
+// Full-text search over list and item titles with `?q=`; returns ranked hits with snippets.
+pub async fn search(
+    query: web::Query<SearchQuery>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!("handler" => "search"));
+
+    let client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::search(&client, &query).await;
+
+    result
+        .map(|hits| hashed_json(&req, &hits))
+        .map_err(log_error(sublog))
+}
+
+// List deleted lists and items that can be restored.
+pub async fn trash(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!("handler" => "trash"));
+
+    let client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::get_trash(&client).await;
+
+    result
+        .map(|trash| hashed_json(&req, &trash))
+        .map_err(log_error(sublog))
+}
+
+// Restore a deleted list or item (`kind` is `list` or `item`) and return it.
+pub async fn restore(
+    path: web::Path<(TrashKind, i32)>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let (kind, id) = path.into_inner();
+    let sublog = state.log.new(o!(
+        "handler" => "restore",
+        "kind" => format!("{:?}", kind),
+        "id" => id
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = match kind {
+        TrashKind::List => db::restore_todo(&mut client, id).await.map(|list| {
+            HttpResponse::Ok()
+                .insert_header(etag(list.version))
+                .json(list)
+        }),
+        TrashKind::Item => db::restore_item(&mut client, id).await.map(|item| {
+            HttpResponse::Ok()
+                .insert_header(etag(item.version))
+                .json(item)
+        }),
+    };
+
+    result.map_err(log_error(sublog))
+}
+
+// List items with a due date across all lists, soonest first. Accepts the same filters as `items`.
+pub async fn due_items(
+    filter: web::Query<ItemFilter>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!("handler" => "due_items"));
+
+    let client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::get_due_items(&client, &filter).await;
+
+    result
+        .map(|items| hashed_json(&req, &items))
+        .map_err(log_error(sublog))
+}
+
 // Fetch a specific item given list and item ids.
 pub async fn get_item(
     params: web::Path<(i32, i32)>,
+    req: HttpRequest,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, AppError> {
     let sublog = state.log.new(o!(
@@ -141,26 +500,347 @@ pub async fn get_item(
     let result = db::get_item(&client, params.0, params.1).await;
 
     result
-        .map(|item| HttpResponse::Ok().json(item))
+        .map(|item| tagged_json(&req, etag(item.version), &item))
         .map_err(log_error(sublog))
 }
 
-// Mark a todo item as checked. Idempotent at DB layer; returns whether a row was updated.
+// Partially update a todo item. Only fields present in the payload are changed.
+pub async fn update_item(
+    params: web::Path<(i32, i32)>,
+    changes: web::Json<UpdateTodoItem>,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "update_item",
+        "list_id" => params.0,
+        "item_id" => params.1,
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::update_item(&mut client, params.0, params.1, &changes, &if_match).await;
+
+    result
+        .map(|item| {
+            HttpResponse::Ok()
+                .insert_header(etag(item.version))
+                .json(item)
+        })
+        .map_err(log_error(sublog))
+}
+
+// Move a todo item, with its sub-items, to another list; returns the item with its new `list_id`.
+pub async fn move_item(
+    params: web::Path<(i32, i32)>,
+    target: web::Json<MoveTodoItem>,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let target_list_id = target.into_inner().list_id;
+    let sublog = state.log.new(o!(
+        "handler" => "move_item",
+        "list_id" => params.0,
+        "item_id" => params.1,
+        "target_list_id" => target_list_id,
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::move_item(&mut client, params.0, params.1, target_list_id, &if_match).await;
+
+    result
+        .map(|item| {
+            HttpResponse::Ok()
+                .insert_header(etag(item.version))
+                .json(item)
+        })
+        .map_err(log_error(sublog))
+}
+
+// Move a todo item and its sub-items to the trash given list and item ids.
+pub async fn delete_item(
+    params: web::Path<(i32, i32)>,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "delete_item",
+        "list_id" => params.0,
+        "item_id" => params.1,
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::delete_item(&mut client, params.0, params.1, &if_match).await;
+
+    result
+        .map(|_| HttpResponse::NoContent().finish())
+        .map_err(log_error(sublog))
+}
+
+// List the recorded changes to an item, newest first.
+pub async fn item_history(
+    params: web::Path<(i32, i32)>,
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "item_history",
+        "list_id" => params.0,
+        "item_id" => params.1,
+    ));
+
+    let client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::get_item_history(&client, params.0, params.1).await;
+
+    result
+        .map(|history| hashed_json(&req, &history))
+        .map_err(log_error(sublog))
+}
+
+// Revert an item's title, checked state, due date, priority and recurrence to an earlier version.
+pub async fn revert_item(
+    params: web::Path<(i32, i32, i32)>,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "revert_item",
+        "list_id" => params.0,
+        "item_id" => params.1,
+        "version" => params.2,
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::revert_item(&mut client, params.0, params.1, params.2, &if_match).await;
+
+    result
+        .map(|item| {
+            HttpResponse::Ok()
+                .insert_header(etag(item.version))
+                .json(item)
+        })
+        .map_err(log_error(sublog))
+}
+
+// Set a todo item's checked state from `{"checked": bool}`, or toggle it when no body is sent.
+// Checking an item also checks its sub-items.
 pub async fn check_todo(
     params: web::Path<(i32, i32)>,
+    body: web::Bytes,
+    if_match: IfMatch,
     state: web::Data<AppState>,
 ) -> Result<impl Responder, AppError> {
+    let checked = optional_json::<CheckTodoItem>(&body)?.map(|check| check.checked);
     let sublog = state.log.new(o!(
         "handler" => "check_todo",
         "list_id" => params.0,
         "item_id" => params.1,
+        "checked" => checked,
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::check_todo(&mut client, params.0, params.1, checked, &if_match).await;
+
+    result
+        .map(|item| {
+            HttpResponse::Ok()
+                .insert_header(etag(item.version))
+                .json(item)
+        })
+        .map_err(log_error(sublog))
+}
+
+// List all tags.
+pub async fn tags(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!("handler" => "tags"));
+
+    let client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::get_tags(&client).await;
+
+    result
+        .map(|tags| hashed_json(&req, &tags))
+        .map_err(log_error(sublog))
+}
+
+// Create a new tag. Names must be unique.
+pub async fn create_tag(
+    tag: web::Json<TagName>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let name = tag.into_inner().name;
+    let sublog = state
+        .log
+        .new(o!("handler" => "create_tag", "tag" => name.clone()));
+
+    let client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::create_tag(&client, &name).await;
+
+    result
+        .map(|tag| HttpResponse::Ok().json(tag))
+        .map_err(log_error(sublog))
+}
+
+// Rename a tag.
+pub async fn rename_tag(
+    tag_id: web::Path<(i32,)>,
+    tag: web::Json<TagName>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let name = tag.into_inner().name;
+    let sublog = state.log.new(o!(
+        "handler" => "rename_tag",
+        "tag_id" => tag_id.0,
+        "tag" => name.clone()
+    ));
+
+    let client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::rename_tag(&client, tag_id.0, &name).await;
+
+    result
+        .map(|tag| HttpResponse::Ok().json(tag))
+        .map_err(log_error(sublog))
+}
+
+// Delete a tag, detaching it from every item.
+pub async fn delete_tag(
+    tag_id: web::Path<(i32,)>,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "delete_tag",
+        "tag_id" => tag_id.0
+    ));
+
+    let client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::delete_tag(&client, tag_id.0).await;
+
+    result
+        .map(|_| HttpResponse::NoContent().finish())
+        .map_err(log_error(sublog))
+}
+
+// Attach a tag to an item; returns the item with its tags.
+pub async fn attach_tag(
+    params: web::Path<(i32, i32, i32)>,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "attach_tag",
+        "list_id" => params.0,
+        "item_id" => params.1,
+        "tag_id" => params.2,
     ));
 
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::attach_tag(&mut client, params.0, params.1, params.2, &if_match).await;
+
+    result
+        .map(|item| {
+            HttpResponse::Ok()
+                .insert_header(etag(item.version))
+                .json(item)
+        })
+        .map_err(log_error(sublog))
+}
+
+// Detach a tag from an item; returns the item with its remaining tags.
+pub async fn detach_tag(
+    params: web::Path<(i32, i32, i32)>,
+    if_match: IfMatch,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!(
+        "handler" => "detach_tag",
+        "list_id" => params.0,
+        "item_id" => params.1,
+        "tag_id" => params.2,
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::detach_tag(&mut client, params.0, params.1, params.2, &if_match).await;
+
+    result
+        .map(|item| {
+            HttpResponse::Ok()
+                .insert_header(etag(item.version))
+                .json(item)
+        })
+        .map_err(log_error(sublog))
+}
+
+// Save a list's items as a reusable template (`{"name": ...}` is optional).
+pub async fn save_template(
+    list_id: web::Path<(i32,)>,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let name = optional_json::<SaveTemplate>(&body)?.and_then(|template| template.name);
+    let sublog = state.log.new(o!(
+        "handler" => "save_template",
+        "list_id" => list_id.0
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::save_template(&mut client, list_id.0, name.as_deref()).await;
+
+    result
+        .map(|template| HttpResponse::Ok().json(template))
+        .map_err(log_error(sublog))
+}
+
+// List all templates with their items.
+pub async fn templates(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let sublog = state.log.new(o!("handler" => "templates"));
+
     let client: Client = get_client(&state.pool, &sublog).await?;
 
-    let result = db::check_todo(&client, params.0, params.1).await;
+    let result = db::get_templates(&client).await;
+
+    result
+        .map(|templates| hashed_json(&req, &templates))
+        .map_err(log_error(sublog))
+}
+
+// Create a new list from a template, filling in `{{placeholders}}` from `{"values": {...}}`.
+pub async fn instantiate_template(
+    template_id: web::Path<(i32,)>,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<impl Responder, AppError> {
+    let instantiate: InstantiateTemplate = optional_json(&body)?.unwrap_or_default();
+    let sublog = state.log.new(o!(
+        "handler" => "instantiate_template",
+        "template_id" => template_id.0
+    ));
+
+    let mut client: Client = get_client(&state.pool, &sublog).await?;
+
+    let result = db::instantiate_template(&mut client, template_id.0, &instantiate).await;
 
     result
-        .map(|updated| HttpResponse::Ok().json(ResultResponse { result: updated }))
+        .map(|todo| {
+            HttpResponse::Ok()
+                .insert_header(etag(todo.version))
+                .json(todo)
+        })
         .map_err(log_error(sublog))
 }
@@ -1,7 +1,10 @@
 use crate::config::Config;
+use crate::db;
 use crate::handlers;
 use crate::models;
+use crate::pagination::{Cursor, Page};
 use actix_web::{test, web, App};
+use chrono::{Duration, Utc};
 use dotenv::dotenv;
 use lazy_static::lazy_static;
 use serde_json::json;
@@ -33,17 +36,17 @@ async fn test_get_todos() {
         .app_data(APP_STATE.clone())
         .route("/todos{_:/?}", web::get().to(handlers::todos));
 
-    let mut app = test::init_service(app).await;
+    let app = test::init_service(app).await;
 
     let req = test::TestRequest::get().uri("/todos/").to_request();
 
-    let response = test::call_service(&mut app, req).await;
+    let response = test::call_service(&app, req).await;
 
     assert_eq!(response.status(), 200, "GET /todos should return 200");
 
     let body = test::read_body(response).await;
 
-    let try_todos: Result<Vec<models::TodoList>, serde_json::error::Error> =
+    let try_todos: Result<Page<models::TodoList>, serde_json::error::Error> =
         serde_json::from_slice(&body);
 
     assert!(try_todos.is_ok(), "Response couldn't not be parsed");
@@ -56,7 +59,7 @@ async fn test_create_todos() {
         .route("/todos{_:/?}", web::get().to(handlers::todos))
         .route("/todos{_:/?}", web::post().to(handlers::create_todo));
 
-    let mut app = test::init_service(app).await;
+    let app = test::init_service(app).await;
 
     let todo_title = "Create todo List";
 
@@ -68,7 +71,7 @@ async fn test_create_todos() {
         .set_payload(create_todo_list.to_string())
         .to_request();
 
-    let response = test::call_service(&mut app, req).await;
+    let response = test::call_service(&app, req).await;
 
     assert_eq!(response.status(), 200, "Status should be 200.");
 
@@ -83,9 +86,2810 @@ async fn test_create_todos() {
 
     let req = test::TestRequest::get().uri("/todos/").to_request();
 
-    let todos: Vec<models::TodoList> = test::call_and_read_body_json(&mut app, req).await;
+    let page: Page<models::TodoList> = test::call_and_read_body_json(&app, req).await;
+    let todos = page.items;
 
     let maybe_list = todos.iter().find(|todo| todo.id == created_list.id);
 
     assert!(maybe_list.is_some(), "Item not found!");
 }
+
+#[actix_rt::test]
+async fn test_update_todo() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}{_:/?}",
+            web::patch().to(handlers::update_todo),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Old title" }))
+        .to_request();
+
+    let created: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/todos/{}", created.id))
+        .set_json(json!({ "title": "New title" }))
+        .to_request();
+
+    let updated: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(updated.id, created.id, "Id should not change");
+    assert_eq!(updated.title, "New title", "Title should be updated");
+
+    let req = test::TestRequest::patch()
+        .uri("/todos/-1")
+        .set_json(json!({ "title": "Missing" }))
+        .to_request();
+
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 404, "Unknown list should return 404");
+}
+
+#[actix_rt::test]
+async fn test_delete_todo() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route("/todos/{list_id}{_:/?}", web::get().to(handlers::get_todo))
+        .route(
+            "/todos/{list_id}{_:/?}",
+            web::delete().to(handlers::delete_todo),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Delete me" }))
+        .to_request();
+
+    let created: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", created.id))
+        .set_json(json!({ "title": "Item in deleted list" }))
+        .to_request();
+
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 200, "Item should be created");
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/todos/{}", created.id))
+        .to_request();
+
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 204, "DELETE should return 204");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}", created.id))
+        .to_request();
+
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 404, "Deleted list should not be found");
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/todos/{}", created.id))
+        .to_request();
+
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 404, "Deleting twice should return 404");
+}
+
+#[actix_rt::test]
+async fn test_delete_item() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::get().to(handlers::get_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::delete().to(handlers::delete_item),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List with item to delete" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Mistaken item" }))
+        .to_request();
+
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/todos/{}/items/{}", list.id + 1, item.id))
+        .to_request();
+
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 404, "Item should be scoped by its list");
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .to_request();
+
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 204, "DELETE should return 204");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .to_request();
+
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 404, "Deleted item should not be found");
+}
+
+#[actix_rt::test]
+async fn test_check_todo() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::put().to(handlers::check_todo),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List with item to check" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Check me" }))
+        .to_request();
+
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+    let uri = format!("/todos/{}/items/{}", list.id, item.id);
+
+    let req = test::TestRequest::put().uri(&uri).to_request();
+    let toggled: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert!(toggled.checked, "Toggling should check the item");
+    assert!(
+        toggled.completed_at.is_some(),
+        "Checked item should have completed_at"
+    );
+
+    let req = test::TestRequest::put().uri(&uri).to_request();
+    let toggled: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert!(!toggled.checked, "Toggling again should uncheck the item");
+    assert!(
+        toggled.completed_at.is_none(),
+        "Unchecked item should clear completed_at"
+    );
+
+    for _ in 0..2 {
+        let req = test::TestRequest::put()
+            .uri(&uri)
+            .set_json(json!({ "checked": true }))
+            .to_request();
+        let checked: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+        assert!(checked.checked, "Explicit check should be idempotent");
+    }
+
+    let req = test::TestRequest::put()
+        .uri(&uri)
+        .set_json(json!({ "checked": "nope" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        400,
+        "A malformed body should be rejected, not toggle the item"
+    );
+
+    // Without a `Content-Type` the body still counts, so this is not a toggle either.
+    let req = test::TestRequest::put()
+        .uri(&uri)
+        .set_payload(r#"{"checked": true}"#)
+        .to_request();
+    let checked: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert!(checked.checked, "The item should still be checked");
+}
+
+#[actix_rt::test]
+async fn test_update_item() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::patch().to(handlers::update_item),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List with item to edit" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Typo" }))
+        .to_request();
+
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+    let uri = format!("/todos/{}/items/{}", list.id, item.id);
+
+    let req = test::TestRequest::patch()
+        .uri(&uri)
+        .set_json(json!({ "title": "Fixed" }))
+        .to_request();
+
+    let updated: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(updated.title, "Fixed", "Title should be updated");
+    assert!(
+        updated.updated_at > item.updated_at,
+        "updated_at should move forward"
+    );
+    assert!(!updated.checked, "Absent fields should be untouched");
+
+    let req = test::TestRequest::patch()
+        .uri(&uri)
+        .set_json(json!({ "title": null }))
+        .to_request();
+
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 400, "Null title should be rejected");
+}
+
+#[actix_rt::test]
+async fn test_due_items() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route("/items/due{_:/?}", web::get().to(handlers::due_items));
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List with deadlines" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let mut created = Vec::new();
+    for (title, due_at) in [
+        ("Overdue", json!("2000-01-01T00:00:00Z")),
+        ("Later", json!("2999-01-01T00:00:00Z")),
+        ("Someday", json!(null)),
+    ] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/todos/{}/items", list.id))
+            .set_json(json!({ "title": title, "due_at": due_at }))
+            .to_request();
+        let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+        created.push(item.id);
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items?overdue=true", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let overdue = page.items;
+
+    assert_eq!(overdue.len(), 1, "Only one item should be overdue");
+    assert_eq!(overdue[0].id, created[0], "The past item should be overdue");
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/todos/{}/items?due_before=2500-01-01T00:00:00Z",
+            list.id
+        ))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let due_before = page.items;
+
+    assert_eq!(due_before.len(), 1, "Only one item is due before 2500");
+
+    let req = test::TestRequest::get().uri("/items/due").to_request();
+    let due: Vec<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert!(
+        due.iter().any(|item| item.id == created[1]),
+        "Due item missing"
+    );
+    assert!(
+        !due.iter().any(|item| item.id == created[2]),
+        "Items without a due date should not be listed"
+    );
+}
+
+#[actix_rt::test]
+async fn test_items_sorted_by_priority() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List with priorities" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    for (title, priority, due_at) in [
+        ("Low", json!("low"), json!(null)),
+        (
+            "Urgent later",
+            json!("urgent"),
+            json!("2999-01-01T00:00:00Z"),
+        ),
+        ("Default", json!(null), json!(null)),
+        (
+            "Urgent soon",
+            json!("urgent"),
+            json!("2998-01-01T00:00:00Z"),
+        ),
+    ] {
+        let mut payload = json!({ "title": title, "due_at": due_at });
+        if !priority.is_null() {
+            payload["priority"] = priority;
+        }
+        let req = test::TestRequest::post()
+            .uri(&format!("/todos/{}/items", list.id))
+            .set_json(payload)
+            .to_request();
+        let response = test::call_service(&app, req).await;
+        assert_eq!(response.status(), 200, "Item should be created");
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items?sort=priority", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+    let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+
+    assert_eq!(
+        titles,
+        vec!["Urgent soon", "Urgent later", "Low", "Default"],
+        "Items should be sorted by priority then due date"
+    );
+    assert_eq!(
+        items[3].priority,
+        models::Priority::None,
+        "Default priority"
+    );
+}
+
+#[actix_rt::test]
+async fn test_item_tags() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/tags/{tag_id}{_:/?}",
+            web::put().to(handlers::attach_tag),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/tags/{tag_id}{_:/?}",
+            web::delete().to(handlers::detach_tag),
+        )
+        .route("/items{_:/?}", web::get().to(handlers::search_items))
+        .route("/tags{_:/?}", web::post().to(handlers::create_tag))
+        .route(
+            "/tags/{tag_id}{_:/?}",
+            web::patch().to(handlers::rename_tag),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List with tags" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let mut items = Vec::new();
+    for title in ["Tagged", "Untagged"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/todos/{}/items", list.id))
+            .set_json(json!({ "title": title }))
+            .to_request();
+        let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+        items.push(item);
+    }
+
+    // Tag names are global, so make them unique per run.
+    let name = format!("chores-{}", list.id);
+    let req = test::TestRequest::post()
+        .uri("/tags")
+        .set_json(json!({ "name": name }))
+        .to_request();
+    let tag: models::Tag = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/tags")
+        .set_json(json!({ "name": name }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        400,
+        "Duplicate tag names should be rejected"
+    );
+
+    let req = test::TestRequest::put()
+        .uri(&format!(
+            "/todos/{}/items/{}/tags/{}",
+            list.id, items[0].id, tag.id
+        ))
+        .to_request();
+    let tagged: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(tagged.tags.len(), 1, "Item should embed its tag");
+    assert_eq!(
+        tagged.tags[0].name, name,
+        "Embedded tag should have its name"
+    );
+
+    let renamed = format!("errands-{}", list.id);
+    let req = test::TestRequest::patch()
+        .uri(&format!("/tags/{}", tag.id))
+        .set_json(json!({ "name": renamed }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 200, "Tag should be renamed");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items?tag={}", list.id, renamed))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let filtered = page.items;
+
+    assert_eq!(filtered.len(), 1, "Only the tagged item should match");
+    assert_eq!(filtered[0].id, items[0].id, "The tagged item should match");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/items?tag={}", renamed))
+        .to_request();
+    let found: Vec<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        found.len(),
+        1,
+        "Cross-list search should find the tagged item"
+    );
+
+    let req = test::TestRequest::delete()
+        .uri(&format!(
+            "/todos/{}/items/{}/tags/{}",
+            list.id, items[0].id, tag.id
+        ))
+        .to_request();
+    let untagged: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert!(untagged.tags.is_empty(), "Tag should be detached");
+}
+
+#[actix_rt::test]
+async fn test_subtasks() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::put().to(handlers::check_todo),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::delete().to(handlers::delete_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/children{_:/?}",
+            web::post().to(handlers::create_child),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List with subtasks" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Parent" }))
+        .to_request();
+    let parent: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/{}/children", list.id, parent.id))
+        .set_json(json!({ "title": "Child" }))
+        .to_request();
+    let child: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        child.parent_id,
+        Some(parent.id),
+        "Child should point to parent"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/{}/children", list.id, child.id))
+        .set_json(json!({ "title": "Grandchild" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 200, "Grandchild should be created");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items?tree=true", list.id))
+        .to_request();
+    let page: Page<models::TodoItemNode> = test::call_and_read_body_json(&app, req).await;
+    let tree = page.items;
+
+    assert_eq!(tree.len(), 1, "Only the parent should be at the top level");
+    assert_eq!(
+        tree[0].children[0].children.len(),
+        1,
+        "Grandchild should nest"
+    );
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, parent.id))
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 200, "Parent should be checked");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+
+    assert!(
+        items.iter().all(|item| item.checked),
+        "Descendants should be checked"
+    );
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/todos/{}/items/{}", list.id, parent.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 204, "Parent should be deleted");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+
+    assert!(
+        items.is_empty(),
+        "Deleting a parent should delete its children"
+    );
+}
+
+#[actix_rt::test]
+async fn test_reorder_items() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/reorder{_:/?}",
+            web::post().to(handlers::reorder_items),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::delete().to(handlers::delete_item),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List to reorder" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    // Create items concurrently: positions must still come out unique and gapless.
+    let responses = futures::future::join_all((0..4).map(|index| {
+        let req = test::TestRequest::post()
+            .uri(&format!("/todos/{}/items", list.id))
+            .set_json(json!({ "title": format!("Item {}", index) }))
+            .to_request();
+        test::call_service(&app, req)
+    }))
+    .await;
+
+    assert!(
+        responses.iter().all(|response| response.status() == 200),
+        "Concurrent creates should succeed"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+    let ids: Vec<i32> = items.iter().map(|item| item.id).collect();
+    let positions: Vec<i32> = items.iter().map(|item| item.position).collect();
+
+    assert_eq!(positions, vec![1, 2, 3, 4], "Positions should be dense");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/reorder", list.id))
+        .set_json(json!({ "item_id": ids[3], "before": ids[0] }))
+        .to_request();
+    let moved: Vec<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let moved_ids: Vec<i32> = moved.iter().map(|item| item.id).collect();
+
+    assert_eq!(
+        moved_ids,
+        vec![ids[3], ids[0], ids[1], ids[2]],
+        "Item should move first"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/reorder", list.id))
+        .set_json(json!({ "order": [ids[2], ids[1], ids[0], ids[3]] }))
+        .to_request();
+    let ordered: Vec<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let ordered_ids: Vec<i32> = ordered.iter().map(|item| item.id).collect();
+
+    assert_eq!(
+        ordered_ids,
+        vec![ids[2], ids[1], ids[0], ids[3]],
+        "Full order applies"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/reorder", list.id))
+        .set_json(json!({ "order": [ids[0]] }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 400, "Partial orders should be rejected");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/reorder", list.id))
+        .set_json(json!({ "item_id": ids[0], "after": ids[3], "version": items[0].version }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        412,
+        "Moving an item that changed since should fail"
+    );
+
+    let current = ordered.iter().find(|item| item.id == ids[0]).unwrap();
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/reorder", list.id))
+        .set_json(json!({ "item_id": ids[0], "after": ids[3], "version": current.version }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 200, "The current version should match");
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/todos/{}/items/{}", list.id, ids[1]))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+    let positions: Vec<i32> = items.iter().map(|item| item.position).collect();
+
+    assert_eq!(positions, vec![1, 2, 3], "Deleting should close the gap");
+}
+
+#[actix_rt::test]
+async fn test_move_item() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/move{_:/?}",
+            web::post().to(handlers::move_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/children{_:/?}",
+            web::post().to(handlers::create_child),
+        );
+
+    let app = test::init_service(app).await;
+
+    let mut lists = Vec::new();
+    for title in ["Move source", "Move target"] {
+        let req = test::TestRequest::post()
+            .uri("/todos/")
+            .set_json(json!({ "title": title }))
+            .to_request();
+        let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+        lists.push(list.id);
+    }
+    let (source, target) = (lists[0], lists[1]);
+
+    for (list_id, title) in [(source, "Stays"), (target, "Already there")] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/todos/{}/items", list_id))
+            .set_json(json!({ "title": title }))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", source))
+        .set_json(json!({ "title": "Moves" }))
+        .to_request();
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/{}/children", source, item.id))
+        .set_json(json!({ "title": "Moves along" }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/{}/move", source, item.id))
+        .set_json(json!({ "list_id": -1 }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        404,
+        "Unknown target list should return 404"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/{}/move", source, item.id))
+        .set_json(json!({ "list_id": target }))
+        .to_request();
+    let moved: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(moved.list_id, target, "Item should be in the target list");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", target))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+    let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+    let positions: Vec<i32> = items.iter().map(|item| item.position).collect();
+
+    assert_eq!(
+        titles,
+        vec!["Already there", "Moves", "Moves along"],
+        "Subtree moved"
+    );
+    assert_eq!(positions, vec![1, 2, 3], "Moved items should be appended");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", source))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+
+    assert_eq!(items.len(), 1, "Only one item should stay behind");
+    assert_eq!(
+        items[0].position, 1,
+        "Source positions should be renumbered"
+    );
+}
+
+#[actix_rt::test]
+async fn test_bulk_items() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/bulk{_:/?}",
+            web::post().to(handlers::bulk_items),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List for bulk operations" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let mut ids = Vec::new();
+    for title in ["Check", "Delete", "Retitle"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/todos/{}/items", list.id))
+            .set_json(json!({ "title": title }))
+            .to_request();
+        let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+        ids.push(item.id);
+    }
+
+    // A failing operation must roll back the ones before it.
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/bulk", list.id))
+        .set_json(json!({ "operations": [
+            { "op": "check", "item_id": ids[0] },
+            { "op": "delete", "item_id": -1 },
+        ] }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 404, "Unknown item should fail the batch");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+
+    assert!(!items[0].checked, "Failed batch should not check anything");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/bulk", list.id))
+        .set_json(json!({ "operations": [
+            { "op": "check", "item_id": ids[0], "version": items[0].version },
+            { "op": "retitle", "item_id": ids[1], "title": "Stale", "version": items[1].version + 1 },
+        ] }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        412,
+        "A stale version should fail the batch"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/bulk", list.id))
+        .set_json(json!({ "operations": [
+            { "op": "check", "item_id": ids[0] },
+            { "op": "delete", "item_id": ids[1] },
+            { "op": "retitle", "item_id": ids[2], "title": "Retitled" },
+        ] }))
+        .to_request();
+    let results: Vec<models::BulkOperationResult> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(results.len(), 3, "Every operation should report a result");
+    assert!(
+        results[0].item.as_ref().unwrap().checked,
+        "Item should be checked"
+    );
+    assert!(results[1].item.is_none(), "Deleted item should be null");
+    assert_eq!(results[2].item.as_ref().unwrap().title, "Retitled");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+    let positions: Vec<i32> = items.iter().map(|item| item.position).collect();
+
+    assert_eq!(positions, vec![1, 2], "Positions should be renumbered");
+}
+
+#[actix_rt::test]
+async fn test_pagination() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::get().to(handlers::todos))
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List with many items" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let mut created = Vec::new();
+    for (title, priority) in [
+        ("One", "low"),
+        ("Two", "high"),
+        ("Three", "low"),
+        ("Four", "urgent"),
+        ("Five", "high"),
+    ] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/todos/{}/items", list.id))
+            .set_json(json!({ "title": title, "priority": priority }))
+            .to_request();
+        let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+        created.push(item.id);
+    }
+
+    for (sort, expected) in [
+        ("", created.clone()),
+        (
+            "&sort=priority",
+            vec![created[3], created[1], created[4], created[0], created[2]],
+        ),
+    ] {
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let uri = match &cursor {
+                Some(cursor) => {
+                    format!("/todos/{}/items?limit=2{}&cursor={}", list.id, sort, cursor)
+                }
+                None => format!("/todos/{}/items?limit=2{}", list.id, sort),
+            };
+            let req = test::TestRequest::get().uri(&uri).to_request();
+            let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+            assert!(page.items.len() <= 2, "Pages should respect the limit");
+            seen.extend(page.items.iter().map(|item| item.id));
+
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        assert_eq!(
+            seen, expected,
+            "Pages should cover every item once, in order"
+        );
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items?limit=5", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(page.items.len(), 5, "Exact fit should return every item");
+    assert!(
+        page.next_cursor.is_none(),
+        "Exact fit should be the last page"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items?limit=2", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/todos/{}/items?limit=2&sort=priority&cursor={}",
+            list.id,
+            page.next_cursor.unwrap()
+        ))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        400,
+        "A cursor from another ordering should be rejected"
+    );
+
+    let tampered = [
+        ("/todos/".to_string(), "newest", vec!["abc"]),
+        (
+            format!("/todos/{}/items", list.id),
+            "position",
+            vec!["1", "x"],
+        ),
+        (
+            format!("/todos/{}/items?sort=priority", list.id),
+            "priority",
+            vec!["someday", "infinity", "1", "1"],
+        ),
+        (
+            format!("/todos/{}/items?sort=priority", list.id),
+            "priority",
+            vec!["high", "yesterday", "1", "1"],
+        ),
+    ];
+    for (uri, order, key) in tampered {
+        let cursor = Cursor {
+            order: order.to_string(),
+            key: key.iter().map(|value| value.to_string()).collect(),
+        };
+        let separator = if uri.contains('?') { '&' } else { '?' };
+        let req = test::TestRequest::get()
+            .uri(&format!("{}{}cursor={}", uri, separator, cursor.encode()))
+            .to_request();
+        let response = test::call_service(&app, req).await;
+
+        assert_eq!(
+            response.status(),
+            400,
+            "Tampered {} cursor should be rejected",
+            order
+        );
+    }
+
+    for uri in ["/todos/?limit=0", "/todos/?cursor=garbage"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let response = test::call_service(&app, req).await;
+
+        assert_eq!(response.status(), 400, "{} should be rejected", uri);
+    }
+
+    let req = test::TestRequest::get().uri("/todos/?limit=1").to_request();
+    let first: Page<models::TodoList> = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/todos/?limit=1&cursor={}",
+            first.next_cursor.unwrap()
+        ))
+        .to_request();
+    let second: Page<models::TodoList> = test::call_and_read_body_json(&app, req).await;
+
+    assert!(
+        second.items[0].id < first.items[0].id,
+        "Lists should page from newest to oldest"
+    );
+}
+
+#[actix_rt::test]
+async fn test_filter_and_sort_items() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::put().to(handlers::check_todo),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List to filter" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let mut created = Vec::new();
+    for title in ["Buy milk", "Call mom", "Buy bread", "50% off MILKSHAKE"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/todos/{}/items", list.id))
+            .set_json(json!({ "title": title }))
+            .to_request();
+        let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+        created.push(item.id);
+    }
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, created[0]))
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(response.status(), 200, "Item should be checked");
+
+    for (query, expected) in [
+        ("checked=false", vec![created[1], created[2], created[3]]),
+        ("q=milk", vec![created[0], created[3]]),
+        ("q=50%25", vec![created[3]]),
+        ("q=milk&checked=false", vec![created[3]]),
+        (
+            "sort=title",
+            vec![created[3], created[2], created[0], created[1]],
+        ),
+        (
+            "sort=-created",
+            vec![created[3], created[2], created[1], created[0]],
+        ),
+        ("sort=position&checked=true", vec![created[0]]),
+        ("q=buy&sort=title&limit=1", vec![created[2]]),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&format!("/todos/{}/items?{}", list.id, query))
+            .to_request();
+        let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+        let ids: Vec<i32> = page.items.iter().map(|item| item.id).collect();
+
+        assert_eq!(ids, expected, "Unexpected items for ?{}", query);
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items?sort=created", list.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 400, "Unknown sort should be rejected");
+}
+
+#[actix_rt::test]
+async fn test_search() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route("/search{_:/?}", web::get().to(handlers::search));
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Xylophonic invoices" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let mut created = Vec::new();
+    for title in ["Send the xylophonic invoice", "Buy xylophonic mallets"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/todos/{}/items", list.id))
+            .set_json(json!({ "title": title }))
+            .to_request();
+        let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+        created.push(item.id);
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/search?q=xylophonic%20invoicing")
+        .to_request();
+    let hits: Vec<models::SearchHit> = test::call_and_read_body_json(&app, req).await;
+    let hits: Vec<&models::SearchHit> = hits.iter().filter(|hit| hit.list_id == list.id).collect();
+
+    assert_eq!(hits.len(), 2, "The list and one item should match");
+    assert_eq!(hits[0].kind, "list", "Ties are broken list first");
+    assert_eq!(
+        hits[1].item_id,
+        Some(created[0]),
+        "Stemmed words should match"
+    );
+    assert_eq!(
+        hits[1].list_title.as_deref(),
+        Some("Xylophonic invoices"),
+        "Item hits carry their list"
+    );
+    assert_eq!(
+        hits[1].snippet, "Send the <b>xylophonic</b> <b>invoice</b>",
+        "Matching terms should be highlighted"
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/search?q=xylophonic%20-invoice")
+        .to_request();
+    let hits: Vec<models::SearchHit> = test::call_and_read_body_json(&app, req).await;
+    let ids: Vec<Option<i32>> = hits
+        .iter()
+        .filter(|hit| hit.list_id == list.id)
+        .map(|hit| hit.item_id)
+        .collect();
+
+    assert_eq!(
+        ids,
+        vec![Some(created[1])],
+        "Excluded terms should not match"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "<img src=x onerror=alert(1)> xylophonic \"tuning\" & co" }))
+        .to_request();
+    let markup: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri("/search?q=xylophonic%20tuning")
+        .to_request();
+    let hits: Vec<models::SearchHit> = test::call_and_read_body_json(&app, req).await;
+    let hit = hits
+        .iter()
+        .find(|hit| hit.item_id == Some(markup.id))
+        .unwrap();
+
+    assert_eq!(
+        hit.snippet,
+        "&lt;img src=x onerror=alert(1)&gt; <b>xylophonic</b> &quot;<b>tuning</b>&quot; &amp; co",
+        "Titles should be escaped in snippets"
+    );
+
+    let req = test::TestRequest::get().uri("/search?q=%20").to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 400, "An empty query should be rejected");
+}
+
+#[actix_rt::test]
+async fn test_trash() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route("/todos/{list_id}{_:/?}", web::get().to(handlers::get_todo))
+        .route(
+            "/todos/{list_id}{_:/?}",
+            web::delete().to(handlers::delete_todo),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::delete().to(handlers::delete_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/children{_:/?}",
+            web::post().to(handlers::create_child),
+        )
+        .route("/trash{_:/?}", web::get().to(handlers::trash))
+        .route(
+            "/trash/{kind}/{id}/restore{_:/?}",
+            web::post().to(handlers::restore),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List for the trash" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Parent" }))
+        .to_request();
+    let parent: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/{}/children", list.id, parent.id))
+        .set_json(json!({ "title": "Child" }))
+        .to_request();
+    let child: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Other" }))
+        .to_request();
+    let other: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/todos/{}/items/{}", list.id, parent.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(response.status(), 204, "Item should be deleted");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let live: Vec<(i32, i32)> = page
+        .items
+        .iter()
+        .map(|item| (item.id, item.position))
+        .collect();
+
+    assert_eq!(live, vec![(other.id, 1)], "Deleted items should be hidden");
+
+    let req = test::TestRequest::get().uri("/trash").to_request();
+    let trash: models::Trash = test::call_and_read_body_json(&app, req).await;
+    let trashed: Vec<i32> = trash
+        .items
+        .iter()
+        .filter(|item| item.list_id == list.id)
+        .map(|item| item.id)
+        .collect();
+
+    assert_eq!(
+        trashed,
+        vec![parent.id],
+        "Sub-items are listed through their parent"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/trash/item/{}/restore", child.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(
+        response.status(),
+        400,
+        "The parent should be restored first"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/trash/item/{}/restore", parent.id))
+        .to_request();
+    let restored: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert!(restored.deleted_at.is_none(), "Item should be restored");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let live: Vec<(i32, i32)> = page
+        .items
+        .iter()
+        .map(|item| (item.id, item.position))
+        .collect();
+
+    assert_eq!(
+        live,
+        vec![(other.id, 1), (parent.id, 2), (child.id, 3)],
+        "Restored items should be appended with their sub-items"
+    );
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/todos/{}", list.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(response.status(), 204, "List should be deleted");
+
+    let req = test::TestRequest::get().uri("/trash").to_request();
+    let trash: models::Trash = test::call_and_read_body_json(&app, req).await;
+
+    assert!(
+        trash.lists.iter().any(|trashed| trashed.id == list.id),
+        "Deleted list should be in the trash"
+    );
+    assert!(
+        trash.items.iter().all(|item| item.list_id != list.id),
+        "Its items come back with it"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/trash/item/{}/restore", other.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(response.status(), 400, "The list should be restored first");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/trash/list/{}/restore", list.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(response.status(), 200, "List should be restored");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        page.items.len(),
+        3,
+        "Items should be restored with the list"
+    );
+
+    for uri in [
+        format!("/trash/list/{}/restore", list.id),
+        format!("/trash/item/{}/restore", other.id),
+        format!("/trash/bin/{}/restore", list.id),
+    ] {
+        let req = test::TestRequest::post().uri(&uri).to_request();
+        let response = test::call_service(&app, req).await;
+
+        assert_eq!(response.status(), 404, "{} should not be found", uri);
+    }
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/todos/{}", list.id))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let client = APP_STATE.pool.get().await.unwrap();
+    let purged = db::purge_trash(&client, Utc::now() + Duration::seconds(1))
+        .await
+        .unwrap();
+
+    assert!(purged >= 1, "Expired trash should be purged");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/trash/list/{}/restore", list.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(response.status(), 404, "Purged lists are gone for good");
+}
+
+#[actix_rt::test]
+async fn test_archive_todo() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::get().to(handlers::todos))
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}{_:/?}",
+            web::patch().to(handlers::update_todo),
+        )
+        .route(
+            "/todos/{list_id}/archive{_:/?}",
+            web::post().to(handlers::archive_todo),
+        )
+        .route(
+            "/todos/{list_id}/unarchive{_:/?}",
+            web::post().to(handlers::unarchive_todo),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::put().to(handlers::check_todo),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Finished project" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    assert!(!list.archived, "New lists should not be archived");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Ship it" }))
+        .to_request();
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/archive", list.id))
+        .to_request();
+    let archived: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    assert!(archived.archived, "List should be archived");
+
+    let req = test::TestRequest::get().uri("/todos/").to_request();
+    let page: Page<models::TodoList> = test::call_and_read_body_json(&app, req).await;
+
+    assert!(
+        page.items.iter().all(|todo| todo.id != list.id),
+        "Archived lists should be hidden by default"
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/todos/?archived=true")
+        .to_request();
+    let page: Page<models::TodoList> = test::call_and_read_body_json(&app, req).await;
+
+    assert!(
+        page.items.iter().any(|todo| todo.id == list.id),
+        "Archived lists should be listed with ?archived=true"
+    );
+    assert!(
+        page.items.iter().all(|todo| todo.archived),
+        "Only archived lists should be listed"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "One more thing" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 400, "Archived lists are read-only");
+
+    let error: serde_json::Value = test::read_body_json(response).await;
+
+    assert_eq!(
+        error["error"],
+        format!(
+            "Todo list {} is archived and read-only. Unarchive it first.",
+            list.id
+        )
+    );
+
+    for req in [
+        test::TestRequest::put()
+            .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+            .to_request(),
+        test::TestRequest::patch()
+            .uri(&format!("/todos/{}", list.id))
+            .set_json(json!({ "title": "Renamed" }))
+            .to_request(),
+    ] {
+        let response = test::call_service(&app, req).await;
+
+        assert_eq!(response.status(), 400, "Archived lists are read-only");
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(page.items.len(), 1, "Archived lists can still be read");
+    assert!(!page.items[0].checked, "Item should be unchanged");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/unarchive", list.id))
+        .to_request();
+    let unarchived: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    assert!(!unarchived.archived, "List should be unarchived");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "One more thing" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        200,
+        "Unarchived lists are writable again"
+    );
+}
+
+#[actix_rt::test]
+async fn test_if_match() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route("/todos/{list_id}{_:/?}", web::get().to(handlers::get_todo))
+        .route(
+            "/todos/{list_id}{_:/?}",
+            web::patch().to(handlers::update_todo),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::get().to(handlers::get_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::patch().to(handlers::update_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::delete().to(handlers::delete_item),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Shared list" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}", list.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    let list_etag = response.headers().get("ETag").unwrap().clone();
+
+    assert_eq!(
+        list_etag, "\"1.0.0\"",
+        "New lists start at version 1, followed by their item counts"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Edited by two people" }))
+        .to_request();
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    let item_etag = response.headers().get("ETag").unwrap().clone();
+
+    // The first teammate saves with the version they read...
+    let req = test::TestRequest::patch()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .insert_header(("If-Match", item_etag.clone()))
+        .set_json(json!({ "title": "First edit" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        200,
+        "Matching version should be accepted"
+    );
+    assert_ne!(
+        response.headers().get("ETag"),
+        Some(&item_etag),
+        "The version should change"
+    );
+
+    // ...and the second one, holding the same stale version, is rejected.
+    let req = test::TestRequest::patch()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .insert_header(("If-Match", item_etag.clone()))
+        .set_json(json!({ "title": "Second edit" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 412, "Stale version should be rejected");
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .insert_header(("If-Match", item_etag))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 412, "Stale deletes should be rejected");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .to_request();
+    let current: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(current.title, "First edit", "Only the first edit applies");
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/todos/{}", list.id))
+        .insert_header(("If-Match", "*"))
+        .set_json(json!({ "title": "Renamed" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 200, "* should match any version");
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/todos/{}", list.id))
+        .insert_header(("If-Match", list_etag))
+        .set_json(json!({ "title": "Renamed again" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        412,
+        "Stale list version should be rejected"
+    );
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/todos/{}", list.id))
+        .set_json(json!({ "title": "Renamed without If-Match" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 200, "If-Match is optional");
+}
+
+#[actix_rt::test]
+async fn test_if_none_match() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::get().to(handlers::get_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::patch().to(handlers::update_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/tags/{tag_id}{_:/?}",
+            web::put().to(handlers::attach_tag),
+        )
+        .route("/tags{_:/?}", web::post().to(handlers::create_tag))
+        .route(
+            "/tags/{tag_id}{_:/?}",
+            web::patch().to(handlers::rename_tag),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Polled list" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Polled item" }))
+        .to_request();
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let item_uri = format!("/todos/{}/items/{}", list.id, item.id);
+    let items_uri = format!("/todos/{}/items", list.id);
+
+    let req = test::TestRequest::get().uri(&item_uri).to_request();
+    let response = test::call_service(&app, req).await;
+    let item_etag = response.headers().get("ETag").unwrap().clone();
+
+    let req = test::TestRequest::get().uri(&items_uri).to_request();
+    let response = test::call_service(&app, req).await;
+    let items_etag = response.headers().get("ETag").unwrap().clone();
+
+    let req = test::TestRequest::get()
+        .uri(&item_uri)
+        .insert_header(("If-None-Match", item_etag.clone()))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 304, "Unchanged item should be a 304");
+    assert_eq!(
+        response.headers().get("ETag"),
+        Some(&item_etag),
+        "304 should repeat the ETag"
+    );
+    assert!(
+        test::read_body(response).await.is_empty(),
+        "304 should have no body"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&items_uri)
+        .insert_header(("If-None-Match", items_etag.clone()))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 304, "Unchanged items should be a 304");
+
+    let req = test::TestRequest::patch()
+        .uri(&item_uri)
+        .set_json(json!({ "title": "Polled and edited" }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&item_uri)
+        .insert_header(("If-None-Match", item_etag.clone()))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 200, "Edited item should be sent again");
+
+    let req = test::TestRequest::get()
+        .uri(&items_uri)
+        .insert_header(("If-None-Match", items_etag))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 200, "Edited items should be sent again");
+
+    // Items embed their tags, so renaming a tag changes the item too.
+    let req = test::TestRequest::post()
+        .uri("/tags")
+        .set_json(json!({ "name": format!("polled-{}", list.id) }))
+        .to_request();
+    let tag: models::Tag = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("{}/tags/{}", item_uri, tag.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    let item_etag = response.headers().get("ETag").unwrap().clone();
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/tags/{}", tag.id))
+        .set_json(json!({ "name": format!("renamed-{}", list.id) }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&item_uri)
+        .insert_header(("If-None-Match", item_etag))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        200,
+        "Renaming a tag should change the item"
+    );
+}
+
+#[actix_rt::test]
+async fn test_item_history() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::put().to(handlers::check_todo),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::patch().to(handlers::update_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/history{_:/?}",
+            web::get().to(handlers::item_history),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/history/{version}/revert{_:/?}",
+            web::post().to(handlers::revert_item),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "List with history" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Original title", "priority": "low" }))
+        .to_request();
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let item_uri = format!("/todos/{}/items/{}", list.id, item.id);
+
+    let req = test::TestRequest::patch()
+        .uri(&item_uri)
+        .set_json(json!({ "title": "Edited title", "priority": "urgent" }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(&item_uri)
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("{}/history", item_uri))
+        .to_request();
+    let history: Vec<models::HistoryEntry> = test::call_and_read_body_json(&app, req).await;
+    let actions: Vec<&str> = history.iter().map(|entry| entry.action.as_str()).collect();
+
+    assert_eq!(
+        actions,
+        ["check", "update", "create"],
+        "Newest change first"
+    );
+    assert_eq!(
+        history[1].old_values.as_ref().unwrap()["title"],
+        "Original title",
+        "Updates should keep the old values"
+    );
+    assert_eq!(
+        history[1].new_values["title"], "Edited title",
+        "Updates should keep the new values"
+    );
+    assert!(
+        history[2].old_values.is_none(),
+        "Creates have no old values"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "{}/history/{}/revert",
+            item_uri, history[2].version
+        ))
+        .to_request();
+    let reverted: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(reverted.title, "Original title", "Title should be reverted");
+    assert_eq!(
+        reverted.priority,
+        models::Priority::Low,
+        "Priority should be reverted"
+    );
+    assert!(!reverted.checked, "Checked state should be reverted");
+    assert!(
+        reverted.version > history[0].version,
+        "Reverting is a change"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("{}/history", item_uri))
+        .to_request();
+    let history: Vec<models::HistoryEntry> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(history.len(), 4, "The revert should be recorded too");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("{}/history/999/revert", item_uri))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        404,
+        "Unknown versions can't be reverted to"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items/{}/history", list.id + 1, item.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 404, "The item must be in the list");
+}
+
+#[actix_rt::test]
+async fn test_recurring_item() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::put().to(handlers::check_todo),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::patch().to(handlers::update_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/children{_:/?}",
+            web::post().to(handlers::create_child),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Chores" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Water plants", "recurrence": "hourly" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 400, "Unknown rules should be rejected");
+
+    let due_at = Utc::now() + Duration::days(1);
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Water plants", "due_at": due_at, "recurrence": "weekly" }))
+        .to_request();
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        item.recurrence.map(|rule| rule.to_string()),
+        Some("FREQ=WEEKLY".to_string()),
+        "Shorthand rules should be stored as RRULEs"
+    );
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    let checked: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert!(
+        checked.recurrence.is_none(),
+        "The rule moves to the next one"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(page.items.len(), 2, "The next occurrence should be added");
+
+    let next = &page.items[1];
+
+    assert_eq!(next.title, "Water plants");
+    assert!(!next.checked, "The next occurrence should be unchecked");
+    assert_eq!(next.recurrence, item.recurrence, "It should keep recurring");
+    assert_eq!(
+        next.due_at.unwrap().timestamp(),
+        (due_at + Duration::weeks(1)).timestamp(),
+        "It should be due a week later"
+    );
+
+    // Unchecking the done one takes its occurrence back, so checking it again only schedules one.
+    for checked in [false, true] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+            .set_json(json!({ "checked": checked }))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        page.items.len(),
+        2,
+        "Checking it again must not schedule a second occurrence"
+    );
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/todos/{}/items/{}", list.id, page.items[1].id))
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        page.items.len(),
+        3,
+        "Only checking the latest occurrence should add another"
+    );
+
+    // Recurring sub-items checked along with their parent are scheduled again too.
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Garden" }))
+        .to_request();
+    let parent: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/{}/children", list.id, parent.id))
+        .set_json(json!({ "title": "Mow the lawn", "recurrence": "weekly" }))
+        .to_request();
+    let child: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, parent.id))
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let mowing: Vec<&models::TodoItem> = page
+        .items
+        .iter()
+        .filter(|item| item.title == "Mow the lawn")
+        .collect();
+
+    assert_eq!(
+        mowing.len(),
+        2,
+        "The sub-item's next occurrence should be added"
+    );
+    assert!(
+        mowing[0].checked && mowing[0].recurrence.is_none(),
+        "The checked sub-item should hand its rule over"
+    );
+    assert_eq!(mowing[1].recurrence, child.recurrence);
+    assert_eq!(
+        mowing[1].parent_id,
+        Some(parent.id),
+        "It should stay under the same parent"
+    );
+}
+
+#[actix_rt::test]
+async fn test_uncheck_recurring_item() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::put().to(handlers::check_todo),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/history/{version}/revert{_:/?}",
+            web::post().to(handlers::revert_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/children{_:/?}",
+            web::post().to(handlers::create_child),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Chores" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Feed the cat", "recurrence": "daily" }))
+        .to_request();
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    // Reverting the accidental check brings the rule back with the item.
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/todos/{}/items/{}/history/{}/revert",
+            list.id, item.id, item.version
+        ))
+        .to_request();
+    let reverted: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert!(!reverted.checked, "The check should be reverted");
+    assert_eq!(
+        reverted.recurrence, item.recurrence,
+        "The rule should come back"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        page.items.len(),
+        1,
+        "The occurrence the check created should be removed"
+    );
+
+    // Once the next occurrence is done too, unchecking the first one leaves the rule with the
+    // latest occurrence.
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, page.items[1].id))
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .set_json(json!({ "checked": false }))
+        .to_request();
+    let unchecked: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert!(
+        unchecked.recurrence.is_none(),
+        "The rule should stay with the latest occurrence"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(page.items.len(), 3, "Completed occurrences are kept");
+    assert_eq!(
+        page.items
+            .iter()
+            .filter(|item| item.recurrence.is_some())
+            .count(),
+        1,
+        "Only one item should recur"
+    );
+
+    // An occurrence the user has added to is kept, and keeps the rule.
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Clean the bathroom", "recurrence": "weekly" }))
+        .to_request();
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let occurrence = page.items.last().unwrap();
+
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/todos/{}/items/{}/children",
+            list.id, occurrence.id
+        ))
+        .set_json(json!({ "title": "Buy cleaner" }))
+        .to_request();
+    let child: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+        .set_json(json!({ "checked": false }))
+        .to_request();
+    let unchecked: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert!(
+        unchecked.recurrence.is_none(),
+        "The rule should stay with the changed occurrence"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let kept = page.items.iter().find(|item| item.id == occurrence.id);
+
+    assert_eq!(
+        kept.map(|item| item.recurrence),
+        Some(item.recurrence),
+        "The occurrence should be kept with its rule"
+    );
+    assert!(
+        page.items.iter().any(|item| item.id == child.id),
+        "Its sub-item should be kept"
+    );
+
+    // Reverting to the check revision checks the item again and hands the rule over again.
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Take out the bins", "recurrence": "weekly" }))
+        .to_request();
+    let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    for checked in [true, false] {
+        let req = test::TestRequest::put()
+            .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+            .set_json(json!({ "checked": checked }))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/todos/{}/items/{}/history/{}/revert",
+            list.id,
+            item.id,
+            item.version + 1
+        ))
+        .to_request();
+    let reverted: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    assert!(reverted.checked, "The check should be restored");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let bins: Vec<&models::TodoItem> = page
+        .items
+        .iter()
+        .filter(|item| item.title == "Take out the bins")
+        .collect();
+
+    assert_eq!(bins.len(), 2, "A next occurrence should be scheduled");
+    assert_eq!(
+        bins.iter().filter(|item| item.recurrence.is_some()).count(),
+        1,
+        "The rule should move to it"
+    );
+}
+
+#[actix_rt::test]
+async fn test_templates() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/children{_:/?}",
+            web::post().to(handlers::create_child),
+        )
+        .route(
+            "/todos/{list_id}/template{_:/?}",
+            web::post().to(handlers::save_template),
+        )
+        .route("/templates{_:/?}", web::get().to(handlers::templates))
+        .route(
+            "/templates/{template_id}/instantiate{_:/?}",
+            web::post().to(handlers::instantiate_template),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Release checklist" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Tag {{version}}", "priority": "high" }))
+        .to_request();
+    let parent: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/{}/children", list.id, parent.id))
+        .set_json(json!({ "title": "Push {{version}} to {{remote}}" }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/template", list.id))
+        .set_json(json!({ "name": "Release {{version}}" }))
+        .to_request();
+    let template: models::Template = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(template.items.len(), 2, "Every item should be saved");
+    assert_eq!(
+        template.items[1].parent_position,
+        Some(template.items[0].position),
+        "Nesting should be saved"
+    );
+
+    let req = test::TestRequest::get().uri("/templates").to_request();
+    let templates: Vec<models::Template> = test::call_and_read_body_json(&app, req).await;
+
+    assert!(
+        templates.iter().any(|saved| saved.id == template.id),
+        "The template should be listed"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/templates/{}/instantiate", template.id))
+        .set_json(json!({ "values": { "version": "1.4" } }))
+        .to_request();
+    let release: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(
+        release.title, "Release 1.4",
+        "The title should be filled in"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", release.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+
+    assert_eq!(items.len(), 2, "Every item should be copied");
+    assert_eq!(items[0].title, "Tag 1.4");
+    assert_eq!(items[0].priority, models::Priority::High);
+    assert_eq!(
+        items[1].title, "Push 1.4 to {{remote}}",
+        "Placeholders without a value are kept"
+    );
+    assert_eq!(
+        items[1].parent_id,
+        Some(items[0].id),
+        "Sub-items should stay nested"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/templates/{}/instantiate", template.id))
+        .set_json(json!({ "values": ["1.4"] }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        400,
+        "Malformed values should be rejected"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/template", list.id))
+        .set_json(json!({ "name": 14 }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 400, "Malformed names should be rejected");
+
+    let req = test::TestRequest::post()
+        .uri("/templates/0/instantiate")
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 404, "Unknown templates can't be used");
+}
+
+#[actix_rt::test]
+async fn test_duplicate_todo() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route(
+            "/todos/{list_id}/duplicate{_:/?}",
+            web::post().to(handlers::duplicate_todo),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::get().to(handlers::items),
+        )
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::put().to(handlers::check_todo),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/children{_:/?}",
+            web::post().to(handlers::create_child),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}/tags/{tag_id}{_:/?}",
+            web::put().to(handlers::attach_tag),
+        )
+        .route("/tags{_:/?}", web::post().to(handlers::create_tag));
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Packing list" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items", list.id))
+        .set_json(json!({ "title": "Toiletries" }))
+        .to_request();
+    let parent: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/items/{}/children", list.id, parent.id))
+        .set_json(json!({ "title": "Toothbrush" }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    // Checking the parent checks the sub-item too.
+    let req = test::TestRequest::put()
+        .uri(&format!("/todos/{}/items/{}", list.id, parent.id))
+        .set_json(json!({ "checked": true }))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri("/tags")
+        .set_json(json!({ "name": format!("travel-{}", list.id) }))
+        .to_request();
+    let tag: models::Tag = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::put()
+        .uri(&format!(
+            "/todos/{}/items/{}/tags/{}",
+            list.id, parent.id, tag.id
+        ))
+        .to_request();
+    test::call_service(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/duplicate", list.id))
+        .to_request();
+    let copy: models::DuplicatedTodoList = test::call_and_read_body_json(&app, req).await;
+
+    assert_ne!(copy.list.id, list.id, "A new list should be created");
+    assert_eq!(copy.list.title, list.title, "The title should be kept");
+    assert_eq!(copy.item_count, 2, "Every item should be copied");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", copy.list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+    let items = page.items;
+
+    assert!(
+        items.iter().all(|item| item.checked),
+        "Checks should be kept"
+    );
+    assert_eq!(
+        items[1].parent_id,
+        Some(items[0].id),
+        "Sub-items should point at the copied parent"
+    );
+    assert_eq!(items[0].tags.len(), 1, "Tags should be copied");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/duplicate", list.id))
+        .set_json(json!({ "title": "Next trip", "reset_checked": true }))
+        .to_request();
+    let copy: models::DuplicatedTodoList = test::call_and_read_body_json(&app, req).await;
+
+    assert_eq!(copy.list.title, "Next trip");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}/items", copy.list.id))
+        .to_request();
+    let page: Page<models::TodoItem> = test::call_and_read_body_json(&app, req).await;
+
+    assert!(
+        page.items
+            .iter()
+            .all(|item| !item.checked && item.completed_at.is_none()),
+        "reset_checked should uncheck every copy"
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/todos/{}/duplicate", list.id))
+        .set_json(json!({ "reset_checked": "yes" }))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        400,
+        "Malformed options should be rejected"
+    );
+
+    let req = test::TestRequest::post()
+        .uri("/todos/0/duplicate")
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(response.status(), 404, "Unknown lists can't be duplicated");
+}
+
+#[actix_rt::test]
+async fn test_list_progress() {
+    let app = App::new()
+        .app_data(APP_STATE.clone())
+        .route("/todos{_:/?}", web::get().to(handlers::todos))
+        .route("/todos{_:/?}", web::post().to(handlers::create_todo))
+        .route("/todos/{list_id}{_:/?}", web::get().to(handlers::get_todo))
+        .route(
+            "/todos/{list_id}/items{_:/?}",
+            web::post().to(handlers::create_item),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::put().to(handlers::check_todo),
+        )
+        .route(
+            "/todos/{list_id}/items/{item_id}{_:/?}",
+            web::delete().to(handlers::delete_item),
+        );
+
+    let app = test::init_service(app).await;
+
+    let req = test::TestRequest::post()
+        .uri("/todos/")
+        .set_json(json!({ "title": "Progress" }))
+        .to_request();
+
+    let list: models::TodoList = test::call_and_read_body_json(&app, req).await;
+
+    let mut items = Vec::new();
+    for title in ["Done", "Not yet", "Also not yet", "Dropped"] {
+        let req = test::TestRequest::post()
+            .uri(&format!("/todos/{}/items", list.id))
+            .set_json(json!({ "title": title }))
+            .to_request();
+        let item: models::TodoItem = test::call_and_read_body_json(&app, req).await;
+        items.push(item);
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}", list.id))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    let list_etag = response.headers().get("ETag").unwrap().clone();
+
+    for (method, item) in [("PUT", &items[0]), ("DELETE", &items[3])] {
+        let req = test::TestRequest::default()
+            .method(method.parse().unwrap())
+            .uri(&format!("/todos/{}/items/{}", list.id, item.id))
+            .set_json(json!({ "checked": true }))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/todos/{}", list.id))
+        .insert_header(("If-None-Match", list_etag))
+        .to_request();
+    let response = test::call_service(&app, req).await;
+
+    assert_eq!(
+        response.status(),
+        200,
+        "Item changes should change the list's ETag"
+    );
+
+    let summary: models::TodoListSummary = test::read_body_json(response).await;
+
+    assert_eq!(summary.item_count, 3, "Trashed items aren't counted");
+    assert_eq!(summary.checked_count, 1);
+    assert_eq!(summary.percent_complete, 33);
+
+    let req = test::TestRequest::get().uri("/todos").to_request();
+    let page: Page<models::TodoListSummary> = test::call_and_read_body_json(&app, req).await;
+    let listed = page
+        .items
+        .iter()
+        .find(|summary| summary.list.id == list.id)
+        .unwrap();
+
+    assert_eq!(
+        (listed.item_count, listed.checked_count),
+        (3, 1),
+        "Listings should carry the same counts"
+    );
+}
@@ -3,16 +3,22 @@
 mod config;
 mod db;
 mod errors;
+mod etag;
 mod handlers;
 mod models;
+mod pagination;
 
-use crate::config::Config;
+use crate::config::{Config, TrashConfig};
+use crate::errors::AppError;
 use crate::handlers::*;
 use crate::models::AppState;
 use actix_web::{middleware, web, App, HttpServer};
+use chrono::Utc;
+use deadpool_postgres::Pool;
 use dotenv::dotenv;
-use slog::info;
+use slog::{error, info, Logger};
 use std::io;
+use std::time::Duration;
 use tokio_postgres::NoTls;
 
 #[actix_rt::main]
@@ -63,6 +69,13 @@ async fn main() -> io::Result<()> {
     // App shared state: injected into handlers via app_data
     let state = web::Data::new(AppState { pool, log });
 
+    // Permanently delete expired trash in the background
+    actix_rt::spawn(purge_trash(
+        state.pool.clone(),
+        config.trash.clone(),
+        state.log.clone(),
+    ));
+
     HttpServer::new(move || {
         // Build the per-worker App, cloning the shared state handle
         App::new()
@@ -72,8 +85,30 @@ async fn main() -> io::Result<()> {
             .route("/todos{_:/?}", web::get().to(todos))
             .route("/todos{_:/?}", web::post().to(create_todo))
             .route("/todos/{list_id}{_:/?}", web::get().to(get_todo))
+            .route("/todos/{list_id}{_:/?}", web::patch().to(update_todo))
+            .route("/todos/{list_id}{_:/?}", web::delete().to(delete_todo))
+            .route(
+                "/todos/{list_id}/archive{_:/?}",
+                web::post().to(archive_todo),
+            )
+            .route(
+                "/todos/{list_id}/unarchive{_:/?}",
+                web::post().to(unarchive_todo),
+            )
+            .route(
+                "/todos/{list_id}/duplicate{_:/?}",
+                web::post().to(duplicate_todo),
+            )
             .route("/todos/{list_id}/items{_:/?}", web::get().to(items))
             .route("/todos/{list_id}/items{_:/?}", web::post().to(create_item))
+            .route(
+                "/todos/{list_id}/items/reorder{_:/?}",
+                web::post().to(reorder_items),
+            )
+            .route(
+                "/todos/{list_id}/items/bulk{_:/?}",
+                web::post().to(bulk_items),
+            )
             .route(
                 "/todos/{list_id}/items/{item_id}{_:/?}",
                 web::get().to(get_item),
@@ -82,6 +117,60 @@ async fn main() -> io::Result<()> {
                 "/todos/{list_id}/items/{item_id}{_:/?}",
                 web::put().to(check_todo),
             )
+            .route(
+                "/todos/{list_id}/items/{item_id}{_:/?}",
+                web::patch().to(update_item),
+            )
+            .route(
+                "/todos/{list_id}/items/{item_id}{_:/?}",
+                web::delete().to(delete_item),
+            )
+            .route(
+                "/todos/{list_id}/items/{item_id}/move{_:/?}",
+                web::post().to(move_item),
+            )
+            .route(
+                "/todos/{list_id}/items/{item_id}/children{_:/?}",
+                web::get().to(children),
+            )
+            .route(
+                "/todos/{list_id}/items/{item_id}/children{_:/?}",
+                web::post().to(create_child),
+            )
+            .route(
+                "/todos/{list_id}/items/{item_id}/history{_:/?}",
+                web::get().to(item_history),
+            )
+            .route(
+                "/todos/{list_id}/items/{item_id}/history/{version}/revert{_:/?}",
+                web::post().to(revert_item),
+            )
+            .route(
+                "/todos/{list_id}/items/{item_id}/tags/{tag_id}{_:/?}",
+                web::put().to(attach_tag),
+            )
+            .route(
+                "/todos/{list_id}/items/{item_id}/tags/{tag_id}{_:/?}",
+                web::delete().to(detach_tag),
+            )
+            .route(
+                "/todos/{list_id}/template{_:/?}",
+                web::post().to(save_template),
+            )
+            .route("/items{_:/?}", web::get().to(search_items))
+            .route("/items/due{_:/?}", web::get().to(due_items))
+            .route("/search{_:/?}", web::get().to(search))
+            .route("/trash{_:/?}", web::get().to(trash))
+            .route("/trash/{kind}/{id}/restore{_:/?}", web::post().to(restore))
+            .route("/templates{_:/?}", web::get().to(templates))
+            .route(
+                "/templates/{template_id}/instantiate{_:/?}",
+                web::post().to(instantiate_template),
+            )
+            .route("/tags{_:/?}", web::get().to(tags))
+            .route("/tags{_:/?}", web::post().to(create_tag))
+            .route("/tags/{tag_id}{_:/?}", web::patch().to(rename_tag))
+            .route("/tags/{tag_id}{_:/?}", web::delete().to(delete_tag))
     })
     // Bind the TCP listener using configured host:port and start the server
     .bind(format!("{}:{}", config.server.host, config.server.port))?
@@ -89,6 +178,37 @@ async fn main() -> io::Result<()> {
     .await
 }
 
+// Every `purge_interval_secs`, remove trash older than `retention_days`. Failures are logged and
+// retried on the next tick.
+async fn purge_trash(pool: Pool, config: TrashConfig, log: Logger) {
+    let mut interval = actix_rt::time::interval(Duration::from_secs(config.purge_interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        // `Config::from_env` bounds the retention, but a failed purge beats a dead purge task.
+        let deleted_before = match chrono::Duration::try_days(config.retention_days)
+            .and_then(|retention| Utc::now().checked_sub_signed(retention))
+        {
+            Some(deleted_before) => deleted_before,
+            None => {
+                error!(log, "Trash retention out of range"; "retention_days" => config.retention_days);
+                continue;
+            }
+        };
+        let result = match pool.get().await {
+            Ok(client) => db::purge_trash(&client, deleted_before).await,
+            Err(err) => Err(AppError::from(err)),
+        };
+
+        match result {
+            Ok(0) => {}
+            Ok(purged) => info!(log, "Purged {} rows from the trash", purged),
+            Err(err) => error!(log, "Error purging trash"; "cause" => err.to_string()),
+        }
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "integration")]
 mod integration_tests;
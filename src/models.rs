@@ -1,8 +1,15 @@
 // File: src/models.rs
 // High-level: Shared data models passed between layers and serialized to/from JSON.
+use bytes::BytesMut;
+use chrono::{DateTime, Duration, Months, Utc};
 use deadpool_postgres::Pool;
-use serde::{Deserialize, Serialize};
-use slog;
+use postgres_types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
 use tokio_pg_mapper_derive::PostgresMapper;
 
 #[derive(Clone)]
@@ -18,6 +25,7 @@ pub struct Status {
 }
 
 // Represents a row in `todo_item`; derives serde for JSON IO and PostgresMapper for row mapping.
+// Timestamps serialize as RFC 3339 strings.
 #[derive(Serialize, Deserialize, PostgresMapper)]
 #[pg_mapper(table = "todo_item")]
 pub struct TodoItem {
@@ -25,6 +33,249 @@ pub struct TodoItem {
     pub list_id: i32,
     pub title: String,
     pub checked: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub priority: Priority,
+    pub parent_id: Option<i32>,
+    // Moves on to the next occurrence when the item is checked.
+    pub recurrence: Option<Recurrence>,
+    // Negative while the item is in the trash.
+    pub position: i32,
+    pub deleted_at: Option<DateTime<Utc>>,
+    // Bumped on every change; sent as the `ETag`.
+    pub version: i32,
+    // Not a column: selected as a `tag[]` subquery alongside each item row.
+    pub tags: Vec<Tag>,
+}
+
+// An item with its nested sub-items, used for tree-shaped listings.
+#[derive(Serialize, Deserialize)]
+pub struct TodoItemNode {
+    #[serde(flatten)]
+    pub item: TodoItem,
+    pub children: Vec<TodoItemNode>,
+}
+
+impl TodoItemNode {
+    // Nest items under their parents, keeping the input order among siblings. Items whose parent
+    // is not part of `items` (e.g. filtered out) are returned as roots.
+    pub fn build_tree(items: Vec<TodoItem>) -> Vec<TodoItemNode> {
+        let ids: HashSet<i32> = items.iter().map(|item| item.id).collect();
+        let mut children: HashMap<i32, Vec<TodoItem>> = HashMap::new();
+        let mut roots = Vec::new();
+
+        for item in items {
+            match item.parent_id {
+                Some(parent_id) if ids.contains(&parent_id) => {
+                    children.entry(parent_id).or_default().push(item)
+                }
+                _ => roots.push(item),
+            }
+        }
+
+        roots
+            .into_iter()
+            .map(|item| TodoItemNode::with_children(item, &mut children))
+            .collect()
+    }
+
+    fn with_children(item: TodoItem, children: &mut HashMap<i32, Vec<TodoItem>>) -> TodoItemNode {
+        let nested = children
+            .remove(&item.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| TodoItemNode::with_children(child, children))
+            .collect();
+
+        TodoItemNode {
+            item,
+            children: nested,
+        }
+    }
+}
+
+// Represents a row in `tag`. Also decodes from the `tag` composite type so items can embed their tags.
+#[derive(Debug, Serialize, Deserialize, PostgresMapper, FromSql)]
+#[pg_mapper(table = "tag")]
+#[postgres(name = "tag")]
+pub struct Tag {
+    pub id: i32,
+    pub name: String,
+}
+
+// Mirrors the `item_priority` Postgres enum; variants are declared from least to most urgent.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, ToSql, FromSql)]
+#[serde(rename_all = "lowercase")]
+#[postgres(name = "item_priority", rename_all = "lowercase")]
+pub enum Priority {
+    #[default]
+    None,
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+// How often a recurring item comes back, stored and sent as an RRULE subset such as
+// `FREQ=WEEKLY;INTERVAL=2`. `daily`, `weekly`, `monthly` and `yearly` are accepted as shorthands.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+// Keeps due dates (and the loop in `next_due`) within chrono's range.
+const MAX_INTERVAL: u32 = 1000;
+
+impl Recurrence {
+    // Due date of the next occurrence: whole intervals after `due_at` (or `now` for items without
+    // one), skipping occurrences that are already past so overdue chores don't pile up.
+    pub fn next_due(&self, due_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> DateTime<Utc> {
+        let start = due_at.unwrap_or(now);
+
+        (1..)
+            .map(|n| self.after(start, n * self.interval))
+            .find(|due| *due > now)
+            .unwrap()
+    }
+
+    fn after(&self, start: DateTime<Utc>, steps: u32) -> DateTime<Utc> {
+        match self.frequency {
+            Frequency::Daily => start + Duration::days(steps.into()),
+            Frequency::Weekly => start + Duration::weeks(steps.into()),
+            // Month ends are clamped, e.g. a month after Jan 31 is Feb 28. Occurrences are scheduled
+            // from the previous due date, so the chain then stays on the 28th (Mar 28, Apr 28...).
+            Frequency::Monthly => start.checked_add_months(Months::new(steps)).unwrap(),
+            Frequency::Yearly => start.checked_add_months(Months::new(12 * steps)).unwrap(),
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid recurrence '{}': use daily, weekly, monthly, yearly or an RRULE such as \
+                 FREQ=WEEKLY;INTERVAL=2.",
+                value
+            )
+        };
+        let frequency = |name: &str| match name {
+            "DAILY" => Ok(Frequency::Daily),
+            "WEEKLY" => Ok(Frequency::Weekly),
+            "MONTHLY" => Ok(Frequency::Monthly),
+            "YEARLY" => Ok(Frequency::Yearly),
+            _ => Err(invalid()),
+        };
+
+        let rule = value.trim().to_ascii_uppercase();
+        let rule = rule.strip_prefix("RRULE:").unwrap_or(&rule);
+
+        if !rule.contains('=') {
+            return Ok(Recurrence {
+                frequency: frequency(rule)?,
+                interval: 1,
+            });
+        }
+
+        let mut recurrence = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+        };
+        let mut has_frequency = false;
+
+        for part in rule.split(';') {
+            match part.split_once('=') {
+                Some(("FREQ", name)) => {
+                    recurrence.frequency = frequency(name)?;
+                    has_frequency = true;
+                }
+                Some(("INTERVAL", interval)) => {
+                    recurrence.interval = interval
+                        .parse()
+                        .ok()
+                        .filter(|interval| (1..=MAX_INTERVAL).contains(interval))
+                        .ok_or_else(invalid)?;
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        match has_frequency {
+            true => Ok(recurrence),
+            false => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+
+        match self.interval {
+            1 => write!(f, "FREQ={}", frequency),
+            interval => write!(f, "FREQ={};INTERVAL={}", frequency, interval),
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(recurrence: Recurrence) -> Self {
+        recurrence.to_string()
+    }
+}
+
+// Stored as text in the same RRULE form it is sent in.
+impl<'a> FromSql<'a> for Recurrence {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn Error + Sync + Send>> {
+        Ok(<&str as FromSql>::from_sql(ty, raw)?.parse()?)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <&str as FromSql>::accepts(ty)
+    }
+}
+
+impl ToSql for Recurrence {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
+        self.to_string().to_sql(ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <String as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
 }
 
 // Represents a row in `todo_list`.
@@ -33,6 +284,47 @@ pub struct TodoItem {
 pub struct TodoList {
     pub id: i32,
     pub title: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    // Archived lists are read-only and only listed with `?archived=true`.
+    pub archived: bool,
+    // Bumped on every change; sent as the `ETag`.
+    pub version: i32,
+}
+
+// A todo list with the progress of its items (trashed ones aren't counted), as listed by
+// `GET /todos` and `GET /todos/{list_id}`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TodoListSummary {
+    #[serde(flatten)]
+    pub list: TodoList,
+    pub item_count: i64,
+    pub checked_count: i64,
+    // Rounded down, so a list only shows 100 once every item is checked; 0 for empty lists.
+    pub percent_complete: i64,
+}
+
+impl TodoListSummary {
+    pub fn new(list: TodoList, item_count: i64, checked_count: i64) -> Self {
+        let percent_complete = match item_count {
+            0 => 0,
+            _ => checked_count * 100 / item_count,
+        };
+
+        TodoListSummary {
+            list,
+            item_count,
+            checked_count,
+            percent_complete,
+        }
+    }
+}
+
+// Query string filter for list listings, e.g. `?archived=true`.
+#[derive(Default, Deserialize)]
+pub struct ListFilter {
+    pub archived: Option<bool>,
 }
 
 // Payload for creating a todo list; kept minimal on purpose.
@@ -41,14 +333,640 @@ pub struct CreateTodoList {
     pub title: String,
 }
 
+// Payload for partially updating a todo list; absent fields are left untouched.
+#[derive(Serialize, Deserialize)]
+pub struct UpdateTodoList {
+    pub title: Option<String>,
+}
+
+// Payload for duplicating a todo list. The copy keeps the original's title unless one is given;
+// `reset_checked` unchecks all of its items.
+#[derive(Default, Serialize, Deserialize)]
+pub struct DuplicateTodoList {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub reset_checked: bool,
+}
+
+// A duplicated todo list and the number of items copied into it.
+#[derive(Serialize, Deserialize)]
+pub struct DuplicatedTodoList {
+    pub list: TodoList,
+    pub item_count: i64,
+}
+
 // Payload for creating a todo item.
 #[derive(Serialize, Deserialize)]
 pub struct CreateTodoItem {
     pub title: String,
+    #[serde(default)]
+    pub due_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
 }
 
-// Generic boolean result wrapper used by update endpoints.
-#[derive(Serialize)]
-pub struct ResultResponse {
-    pub result: bool,
+// Payload for creating or renaming a tag.
+#[derive(Serialize, Deserialize)]
+pub struct TagName {
+    pub name: String,
+}
+
+// Partial update payload for a todo item. The outer `Option` tells whether the field was sent
+// at all; the inner one whether it was sent as `null`.
+#[derive(Default, Serialize, Deserialize)]
+pub struct UpdateTodoItem {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub title: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub checked: Option<Option<bool>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub priority: Option<Option<Priority>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub recurrence: Option<Option<Recurrence>>,
+}
+
+// Query string filters for item listings, e.g. `?due_before=2020-03-01T00:00:00Z&overdue=true&tag=home`
+// or `?checked=false&q=milk&sort=title`.
+#[derive(Default, Deserialize)]
+pub struct ItemFilter {
+    pub due_before: Option<DateTime<Utc>>,
+    pub overdue: Option<bool>,
+    pub tag: Option<String>,
+    pub checked: Option<bool>,
+    // Case-insensitive substring of the title.
+    pub q: Option<String>,
+    pub sort: Option<ItemSort>,
+    // Not a filter: nests children under their parents instead of returning a flat list.
+    pub tree: Option<bool>,
+}
+
+// Query string for `GET /search`, e.g. `?q=invoice` or `?q="quarterly invoice" -paid&limit=10`.
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+}
+
+// A list or item matching a search, with its list for context. `snippet` is the matching title,
+// HTML-escaped, with the search terms wrapped in `<b>` tags.
+#[derive(Serialize, Deserialize, PostgresMapper)]
+#[pg_mapper(table = "search_hit")]
+pub struct SearchHit {
+    // "list" or "item".
+    pub kind: String,
+    pub list_id: i32,
+    pub list_title: Option<String>,
+    pub item_id: Option<i32>,
+    pub title: Option<String>,
+    pub snippet: String,
+    pub rank: f32,
+}
+
+// Ordering modes for item listings; without one, items keep their list order.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemSort {
+    // List order; the default.
+    Position,
+    // Most urgent first, then soonest due date.
+    Priority,
+    // Alphabetical by title.
+    Title,
+    // Newest first.
+    #[serde(rename = "-created")]
+    Created,
+}
+
+// Payload for applying several item operations at once, e.g.
+// `{"operations": [{"op": "check", "item_id": 1}, {"op": "retitle", "item_id": 2, "title": "New"}]}`.
+// Each operation may carry the item `version` it expects, which works like `If-Match` for that item.
+#[derive(Serialize, Deserialize)]
+pub struct BulkItems {
+    pub operations: Vec<BulkOperation>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BulkOperation {
+    Check {
+        item_id: i32,
+        version: Option<i32>,
+    },
+    Uncheck {
+        item_id: i32,
+        version: Option<i32>,
+    },
+    Delete {
+        item_id: i32,
+        version: Option<i32>,
+    },
+    Retitle {
+        item_id: i32,
+        title: String,
+        version: Option<i32>,
+    },
+}
+
+impl BulkOperation {
+    pub fn item_id(&self) -> i32 {
+        match self {
+            BulkOperation::Check { item_id, .. }
+            | BulkOperation::Uncheck { item_id, .. }
+            | BulkOperation::Delete { item_id, .. }
+            | BulkOperation::Retitle { item_id, .. } => *item_id,
+        }
+    }
+
+    pub fn version(&self) -> Option<i32> {
+        match self {
+            BulkOperation::Check { version, .. }
+            | BulkOperation::Uncheck { version, .. }
+            | BulkOperation::Delete { version, .. }
+            | BulkOperation::Retitle { version, .. } => *version,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BulkOperation::Check { .. } => "check",
+            BulkOperation::Uncheck { .. } => "uncheck",
+            BulkOperation::Delete { .. } => "delete",
+            BulkOperation::Retitle { .. } => "retitle",
+        }
+    }
+}
+
+// Outcome of one bulk operation; `item` is the resulting item, or `null` once deleted.
+#[derive(Serialize, Deserialize)]
+pub struct BulkOperationResult {
+    pub op: String,
+    pub item_id: i32,
+    pub item: Option<TodoItem>,
+}
+
+// Contents of the trash, most recently deleted first. Items deleted together with their list (or
+// parent item) are only listed through it and come back when it is restored.
+#[derive(Serialize, Deserialize)]
+pub struct Trash {
+    pub lists: Vec<TodoList>,
+    pub items: Vec<TodoItem>,
+}
+
+// What a trash entry is, as it appears in `/trash/{kind}/{id}/restore`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashKind {
+    List,
+    Item,
+}
+
+// A saved list template with its items in order.
+#[derive(Serialize, Deserialize, PostgresMapper)]
+#[pg_mapper(table = "template")]
+pub struct Template {
+    pub id: i32,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    // Not a column: selected as a `template_item[]` subquery, like `TodoItem::tags`.
+    pub items: Vec<TemplateItem>,
+}
+
+// An item of a template. Sub-items refer to their parent by its position.
+#[derive(Debug, Serialize, Deserialize, FromSql)]
+#[postgres(name = "template_item")]
+pub struct TemplateItem {
+    pub template_id: i32,
+    pub position: i32,
+    pub parent_position: Option<i32>,
+    pub title: String,
+    pub priority: Priority,
+    pub recurrence: Option<Recurrence>,
+}
+
+// Payload for saving a list as a template; the name defaults to the list's title.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SaveTemplate {
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+// Payload for creating a list from a template; the title defaults to the template's name.
+// `{{name}}` placeholders in the titles are filled from `values`, e.g.
+// `{"values": {"version": "1.4"}}`; ones without a value are left as they are.
+#[derive(Default, Serialize, Deserialize)]
+pub struct InstantiateTemplate {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub values: HashMap<String, String>,
+}
+
+impl InstantiateTemplate {
+    pub fn fill(&self, text: &str) -> String {
+        let mut filled = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find("{{") {
+            let end = match rest[start..].find("}}") {
+                Some(end) => start + end + 2,
+                None => break,
+            };
+            let placeholder = &rest[start..end];
+
+            filled.push_str(&rest[..start]);
+            match self
+                .values
+                .get(placeholder[2..placeholder.len() - 2].trim())
+            {
+                Some(value) => filled.push_str(value),
+                None => filled.push_str(placeholder),
+            }
+            rest = &rest[end..];
+        }
+
+        filled.push_str(rest);
+        filled
+    }
+}
+
+// One recorded change to a list or item: its `version` afterwards and the values before and after,
+// as JSON. `action` is one of create, update, check, uncheck, archive, unarchive, delete or restore.
+#[derive(Serialize, Deserialize, PostgresMapper)]
+#[pg_mapper(table = "history")]
+pub struct HistoryEntry {
+    pub id: i32,
+    pub action: String,
+    pub version: i32,
+    pub old_values: Option<serde_json::Value>,
+    pub new_values: serde_json::Value,
+    pub changed_at: DateTime<Utc>,
+}
+
+// Payload for moving an item to another list.
+#[derive(Serialize, Deserialize)]
+pub struct MoveTodoItem {
+    pub list_id: i32,
+}
+
+// Payload for rearranging a list's items: either move one item right before or after another,
+// e.g. `{"item_id": 3, "before": 1}`, optionally with the moved item's expected `version`, or submit
+// every item id in the desired order.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ReorderItems {
+    Move {
+        item_id: i32,
+        before: Option<i32>,
+        after: Option<i32>,
+        version: Option<i32>,
+    },
+    Order {
+        order: Vec<i32>,
+    },
+}
+
+impl ReorderItems {
+    // Compute the new id order from the current one, or explain why the request is invalid.
+    pub fn apply(&self, current: &[i32]) -> Result<Vec<i32>, String> {
+        match self {
+            ReorderItems::Move {
+                item_id,
+                before,
+                after,
+                ..
+            } => {
+                let (anchor, offset) = match (before, after) {
+                    (Some(anchor), None) => (*anchor, 0),
+                    (None, Some(anchor)) => (*anchor, 1),
+                    _ => return Err("Exactly one of 'before' or 'after' is required.".to_string()),
+                };
+                if anchor == *item_id {
+                    return Err("An item cannot be moved relative to itself.".to_string());
+                }
+                if !current.contains(item_id) {
+                    return Err(format!("Item {} is not in this list.", item_id));
+                }
+
+                let mut order: Vec<i32> =
+                    current.iter().copied().filter(|id| id != item_id).collect();
+                let index = order
+                    .iter()
+                    .position(|id| *id == anchor)
+                    .ok_or_else(|| format!("Item {} is not in this list.", anchor))?;
+                order.insert(index + offset, *item_id);
+
+                Ok(order)
+            }
+            ReorderItems::Order { order } => {
+                let mut submitted = order.clone();
+                let mut expected = current.to_vec();
+                submitted.sort_unstable();
+                expected.sort_unstable();
+
+                if submitted != expected {
+                    return Err(
+                        "The order must list every item of the list exactly once.".to_string()
+                    );
+                }
+
+                Ok(order.clone())
+            }
+        }
+    }
+}
+
+// Wraps any present value (including `null`) in `Some`, so absent fields stay `None` via `default`.
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Deserialize::deserialize(deserializer).map(Some)
+}
+
+// Payload for setting an item's checked state explicitly instead of toggling it.
+#[derive(Serialize, Deserialize)]
+pub struct CheckTodoItem {
+    pub checked: bool,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{
+        Frequency, InstantiateTemplate, ItemFilter, ItemSort, Priority, Recurrence, ReorderItems,
+        TodoItem, TodoItemNode, TodoList, TodoListSummary, UpdateTodoItem,
+    };
+    use actix_web::web::Query;
+    use chrono::{TimeZone, Utc};
+
+    fn item(id: i32, parent_id: Option<i32>) -> TodoItem {
+        TodoItem {
+            id,
+            list_id: 1,
+            title: format!("Item {}", id),
+            checked: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            completed_at: None,
+            due_at: None,
+            priority: Priority::None,
+            parent_id,
+            recurrence: None,
+            position: id,
+            deleted_at: None,
+            version: 1,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_update_item_absent_fields() {
+        let update: UpdateTodoItem = serde_json::from_str("{}").unwrap();
+
+        assert!(update.title.is_none(), "Absent title should be None");
+        assert!(update.checked.is_none(), "Absent checked should be None");
+    }
+
+    #[test]
+    fn test_update_item_null_vs_value() {
+        let update: UpdateTodoItem =
+            serde_json::from_str(r#"{"title": null, "checked": true}"#).unwrap();
+
+        assert_eq!(update.title, Some(None), "Null title should be Some(None)");
+        assert_eq!(update.checked, Some(Some(true)), "Value should be kept");
+    }
+
+    #[test]
+    fn test_priority_json_names() {
+        let priority: Priority = serde_json::from_str(r#""urgent""#).unwrap();
+
+        assert_eq!(priority, Priority::Urgent, "Priorities should be lowercase");
+        assert_eq!(
+            serde_json::to_string(&Priority::default()).unwrap(),
+            r#""none""#,
+            "Default priority should be none"
+        );
+    }
+
+    #[test]
+    fn test_item_filter_query() {
+        let filter = Query::<ItemFilter>::from_query("checked=false&q=milk&sort=-created").unwrap();
+
+        assert_eq!(filter.checked, Some(false));
+        assert_eq!(filter.q.as_deref(), Some("milk"));
+        assert_eq!(
+            filter.sort,
+            Some(ItemSort::Created),
+            "-created is newest first"
+        );
+
+        let invalid = Query::<ItemFilter>::from_query("sort=created");
+        assert!(invalid.is_err(), "Unknown sort orders should be rejected");
+    }
+
+    #[test]
+    fn test_build_tree() {
+        let items = vec![
+            item(1, None),
+            item(2, Some(1)),
+            item(3, Some(2)),
+            item(4, Some(1)),
+        ];
+
+        let tree = TodoItemNode::build_tree(items);
+
+        assert_eq!(tree.len(), 1, "Only the top-level item should be a root");
+        assert_eq!(tree[0].children.len(), 2, "Root should have two children");
+        assert_eq!(
+            tree[0].children[0].item.id, 2,
+            "Siblings should keep their order"
+        );
+        assert_eq!(
+            tree[0].children[0].children[0].item.id, 3,
+            "Grandchild should nest"
+        );
+    }
+
+    #[test]
+    fn test_build_tree_orphans_become_roots() {
+        let tree = TodoItemNode::build_tree(vec![item(2, Some(1)), item(3, Some(2))]);
+
+        assert_eq!(tree.len(), 1, "Item with a missing parent should be a root");
+        assert_eq!(tree[0].item.id, 2, "The orphan should be the root");
+    }
+
+    #[test]
+    fn test_reorder_move_before_and_after() {
+        let current = [1, 2, 3, 4];
+
+        let before: ReorderItems = serde_json::from_str(r#"{"item_id": 4, "before": 2}"#).unwrap();
+        let after: ReorderItems = serde_json::from_str(r#"{"item_id": 1, "after": 4}"#).unwrap();
+
+        assert_eq!(before.apply(&current), Ok(vec![1, 4, 2, 3]));
+        assert_eq!(after.apply(&current), Ok(vec![2, 3, 4, 1]));
+    }
+
+    #[test]
+    fn test_reorder_invalid_moves() {
+        let current = [1, 2, 3];
+
+        let both: ReorderItems =
+            serde_json::from_str(r#"{"item_id": 1, "before": 2, "after": 3}"#).unwrap();
+        let unknown: ReorderItems = serde_json::from_str(r#"{"item_id": 1, "before": 9}"#).unwrap();
+
+        assert!(both.apply(&current).is_err(), "Only one anchor is allowed");
+        assert!(
+            unknown.apply(&current).is_err(),
+            "Anchor must be in the list"
+        );
+    }
+
+    #[test]
+    fn test_reorder_full_order() {
+        let current = [1, 2, 3];
+
+        let valid: ReorderItems = serde_json::from_str(r#"{"order": [3, 1, 2]}"#).unwrap();
+        let partial: ReorderItems = serde_json::from_str(r#"{"order": [3, 1]}"#).unwrap();
+        let duplicated: ReorderItems = serde_json::from_str(r#"{"order": [3, 3, 1]}"#).unwrap();
+
+        assert_eq!(valid.apply(&current), Ok(vec![3, 1, 2]));
+        assert!(
+            partial.apply(&current).is_err(),
+            "Every item must be listed"
+        );
+        assert!(duplicated.apply(&current).is_err(), "Ids must not repeat");
+    }
+
+    #[test]
+    fn test_recurrence_parse() {
+        let weekly: Recurrence = "weekly".parse().unwrap();
+        let biweekly: Recurrence = "RRULE:FREQ=WEEKLY;INTERVAL=2".parse().unwrap();
+
+        assert_eq!(weekly.frequency, Frequency::Weekly);
+        assert_eq!(
+            weekly.to_string(),
+            "FREQ=WEEKLY",
+            "Shorthands become RRULEs"
+        );
+        assert_eq!(biweekly.interval, 2);
+        assert_eq!(biweekly.to_string(), "FREQ=WEEKLY;INTERVAL=2");
+
+        for invalid in [
+            "hourly",
+            "INTERVAL=2",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;COUNT=3",
+        ] {
+            assert!(
+                invalid.parse::<Recurrence>().is_err(),
+                "{} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_recurrence_next_due() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 10, 12, 0, 0).unwrap();
+        let monthly: Recurrence = "monthly".parse().unwrap();
+        let daily: Recurrence = "FREQ=DAILY;INTERVAL=3".parse().unwrap();
+
+        assert_eq!(
+            monthly.next_due(
+                Some(Utc.with_ymd_and_hms(2026, 3, 31, 9, 0, 0).unwrap()),
+                now
+            ),
+            Utc.with_ymd_and_hms(2026, 4, 30, 9, 0, 0).unwrap(),
+            "Month ends are clamped"
+        );
+        assert_eq!(
+            monthly.next_due(
+                Some(Utc.with_ymd_and_hms(2026, 4, 30, 9, 0, 0).unwrap()),
+                now
+            ),
+            Utc.with_ymd_and_hms(2026, 5, 30, 9, 0, 0).unwrap(),
+            "The next one follows from the clamped date"
+        );
+        assert_eq!(
+            daily.next_due(
+                Some(Utc.with_ymd_and_hms(2026, 3, 1, 9, 0, 0).unwrap()),
+                now
+            ),
+            Utc.with_ymd_and_hms(2026, 3, 13, 9, 0, 0).unwrap(),
+            "Missed occurrences are skipped"
+        );
+        assert_eq!(
+            daily.next_due(None, now),
+            Utc.with_ymd_and_hms(2026, 3, 13, 12, 0, 0).unwrap(),
+            "Without a due date, count from now"
+        );
+    }
+
+    #[test]
+    fn test_fill_placeholders() {
+        let instantiate: InstantiateTemplate =
+            serde_json::from_str(r#"{"values": {"version": "1.4", "owner": "Sam"}}"#).unwrap();
+
+        assert_eq!(
+            instantiate.fill("Release {{version}} ({{ owner }})"),
+            "Release 1.4 (Sam)"
+        );
+        assert_eq!(
+            instantiate.fill("Tag {{version}}, ping {{team}}"),
+            "Tag 1.4, ping {{team}}",
+            "Unknown placeholders are kept"
+        );
+        assert_eq!(instantiate.fill("Unclosed {{version"), "Unclosed {{version");
+    }
+
+    #[test]
+    fn test_list_summary_percent_complete() {
+        let list = |id| TodoList {
+            id,
+            title: format!("List {}", id),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            deleted_at: None,
+            archived: false,
+            version: 1,
+        };
+
+        assert_eq!(TodoListSummary::new(list(1), 12, 7).percent_complete, 58);
+        assert_eq!(
+            TodoListSummary::new(list(2), 200, 199).percent_complete,
+            99,
+            "Only 100 once everything is checked"
+        );
+        assert_eq!(
+            TodoListSummary::new(list(3), 0, 0).percent_complete,
+            0,
+            "Empty lists are at 0"
+        );
+    }
 }
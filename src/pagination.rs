@@ -0,0 +1,138 @@
+// File: src/pagination.rs
+// High-level: Cursor-based pagination shared by listing endpoints: the `limit`/`cursor` query
+// extractor, the opaque cursor encoding, and the response envelope.
+use crate::errors::{AppError, AppErrorType};
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use futures::future::{ready, Ready};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_LIMIT: i64 = 50;
+pub const MAX_LIMIT: i64 = 200;
+
+// Position of the last row of a page: the name of the ordering it was taken from, plus that row's
+// sort key values as text. Serialized to JSON and base64 so clients treat it as opaque.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub order: String,
+    pub key: Vec<String>,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap())
+    }
+
+    pub fn decode(cursor: &str) -> Result<Cursor, AppError> {
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| bad_request("Invalid cursor."))
+    }
+}
+
+#[derive(Deserialize)]
+struct PaginationQuery {
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+// Extractor for `?limit=&cursor=`. Unrelated query parameters are ignored, so it can be combined
+// with other `web::Query` extractors on the same handler.
+#[derive(Debug)]
+pub struct Pagination {
+    pub limit: i64,
+    pub cursor: Option<Cursor>,
+}
+
+impl Pagination {
+    pub fn from_query(query: &str) -> Result<Pagination, AppError> {
+        let query = web::Query::<PaginationQuery>::from_query(query)
+            .map_err(|err| bad_request(&err.to_string()))?
+            .into_inner();
+
+        let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+        if !(1..=MAX_LIMIT).contains(&limit) {
+            return Err(bad_request(&format!(
+                "'limit' must be between 1 and {}.",
+                MAX_LIMIT
+            )));
+        }
+
+        let cursor = query.cursor.as_deref().map(Cursor::decode).transpose()?;
+
+        Ok(Pagination { limit, cursor })
+    }
+}
+
+impl FromRequest for Pagination {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Pagination::from_query(req.query_string()))
+    }
+}
+
+// Response envelope for paginated listings; `next_cursor` is `null` on the last page.
+#[derive(Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+fn bad_request(message: &str) -> AppError {
+    AppError {
+        error_type: AppErrorType::BadRequestError,
+        cause: None,
+        message: Some(message.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{Cursor, Pagination, DEFAULT_LIMIT};
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor {
+            order: "position".to_string(),
+            key: vec!["3".to_string(), "Groceries & more".to_string()],
+        };
+
+        let decoded = Cursor::decode(&cursor.encode()).unwrap();
+
+        assert_eq!(decoded, cursor, "Cursor should survive encoding");
+    }
+
+    #[test]
+    fn test_invalid_cursor() {
+        assert!(
+            Cursor::decode("not a cursor").is_err(),
+            "Garbage is rejected"
+        );
+    }
+
+    #[test]
+    fn test_pagination_defaults() {
+        let pagination = Pagination::from_query("sort=priority").unwrap();
+
+        assert_eq!(
+            pagination.limit, DEFAULT_LIMIT,
+            "Default limit should apply"
+        );
+        assert!(pagination.cursor.is_none(), "No cursor on the first page");
+    }
+
+    #[test]
+    fn test_pagination_limit_bounds() {
+        assert!(
+            Pagination::from_query("limit=0").is_err(),
+            "Zero is rejected"
+        );
+        assert!(Pagination::from_query("limit=1000").is_err(), "Too large");
+        assert_eq!(Pagination::from_query("limit=10").unwrap().limit, 10);
+    }
+}
@@ -1,26 +1,126 @@
 // @generated automatically by Diesel CLI.
 
+pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "item_priority"))]
+    pub struct ItemPriority;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tsvector", schema = "pg_catalog"))]
+    pub struct Tsvector;
+}
+
+diesel::table! {
+    history (id) {
+        id -> Int4,
+        list_id -> Nullable<Int4>,
+        item_id -> Nullable<Int4>,
+        #[max_length = 16]
+        action -> Varchar,
+        version -> Int4,
+        old_values -> Nullable<Jsonb>,
+        new_values -> Jsonb,
+        changed_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    tag (id) {
+        id -> Int4,
+        #[max_length = 50]
+        name -> Varchar,
+    }
+}
+
+diesel::table! {
+    template (id) {
+        id -> Int4,
+        #[max_length = 150]
+        name -> Varchar,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ItemPriority;
+
+    template_item (template_id, position) {
+        template_id -> Int4,
+        position -> Int4,
+        parent_position -> Nullable<Int4>,
+        #[max_length = 150]
+        title -> Varchar,
+        priority -> ItemPriority,
+        #[max_length = 100]
+        recurrence -> Nullable<Varchar>,
+    }
+}
+
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::ItemPriority;
+    use super::sql_types::Tsvector;
+
     todo_item (id) {
         id -> Int4,
         #[max_length = 150]
         title -> Varchar,
         checked -> Bool,
         list_id -> Int4,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        completed_at -> Nullable<Timestamptz>,
+        due_at -> Nullable<Timestamptz>,
+        priority -> ItemPriority,
+        parent_id -> Nullable<Int4>,
+        position -> Int4,
+        search -> Tsvector,
+        deleted_at -> Nullable<Timestamptz>,
+        version -> Int4,
+        #[max_length = 100]
+        recurrence -> Nullable<Varchar>,
+        previous_occurrence_id -> Nullable<Int4>,
+    }
+}
+
+diesel::table! {
+    todo_item_tag (item_id, tag_id) {
+        item_id -> Int4,
+        tag_id -> Int4,
     }
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::Tsvector;
+
     todo_list (id) {
         id -> Int4,
         #[max_length = 150]
         title -> Nullable<Varchar>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        search -> Tsvector,
+        deleted_at -> Nullable<Timestamptz>,
+        archived -> Bool,
+        version -> Int4,
     }
 }
 
+diesel::joinable!(history -> todo_item (item_id));
+diesel::joinable!(history -> todo_list (list_id));
+diesel::joinable!(template_item -> template (template_id));
 diesel::joinable!(todo_item -> todo_list (list_id));
+diesel::joinable!(todo_item_tag -> tag (tag_id));
+diesel::joinable!(todo_item_tag -> todo_item (item_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    history,
+    tag,
+    template,
+    template_item,
     todo_item,
+    todo_item_tag,
     todo_list,
 );